@@ -0,0 +1,260 @@
+//! Detect and follow alias pages.
+//!
+//! Many pages only point to another page, e.g. `egrep`:
+//!
+//! ```md
+//! # egrep
+//!
+//! > This command is an alias of `grep -E`.
+//!
+//! - View documentation for the original command:
+//!
+//! `tldr grep`
+//! ```
+
+use crate::cache::PageLookupResult;
+use crate::tokenizer::Tokenizer;
+use crate::types::LineType;
+
+/// The page that an alias page points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasTarget {
+    pub name: String,
+    /// The platform given with `-p`, e.g. `common` for `tldr curl -p common`
+    pub platform: Option<String>,
+}
+
+/// Parse the arguments of a `tldr` command, e.g. `gh codespace` or
+/// `curl -p common`. Placeholders and unknown flags mean that the command is
+/// not a reference to a single page.
+fn parse_tldr_command(args: &str) -> Option<AliasTarget> {
+    let mut words = Vec::new();
+    let mut platform = None;
+    let mut args = args.split_whitespace();
+    while let Some(arg) = args.next() {
+        match arg {
+            "-p" | "--platform" => platform = Some(args.next()?.to_string()),
+            _ if arg.starts_with('-') || arg.contains("{{") => return None,
+            _ => words.push(arg),
+        }
+    }
+    if words.is_empty() {
+        return None;
+    }
+    Some(AliasTarget {
+        name: words.join("-").to_lowercase(),
+        platform,
+    })
+}
+
+/// Return the page that an alias page on the given platform points to, or
+/// `None` if the page is not an alias page.
+///
+/// Alias pages consist of a single example that shows the page of the
+/// original command (e.g. `tldr grep`), whatever their language. A page that
+/// points to itself is not an alias page.
+pub fn alias_target(tokens: &[LineType], platform: Option<&str>) -> Option<AliasTarget> {
+    let mut examples = tokens.iter().filter_map(|token| match token {
+        LineType::ExampleCode(code) => Some(code.as_str()),
+        _ => None,
+    });
+    let target = match (examples.next(), examples.next()) {
+        (Some(code), None) => parse_tldr_command(code.trim().strip_prefix("tldr ")?)?,
+        _ => return None,
+    };
+
+    let name = tokens.iter().find_map(|token| match token {
+        LineType::Title(title) => parse_tldr_command(title).map(|title| title.name),
+        _ => None,
+    });
+    let same_platform = target.platform.is_none() || target.platform.as_deref() == platform;
+    if same_platform && name.as_ref() == Some(&target.name) {
+        return None;
+    }
+    Some(target)
+}
+
+/// Return the page that an alias page points to. Patches are not taken into
+/// account.
+pub fn page_alias(page: &PageLookupResult) -> Option<AliasTarget> {
+    let content = page.contents().next()?;
+    let mut tokenizer = Tokenizer::new(content.reader().ok()?);
+    let mut tokens = Vec::new();
+    while let Ok(Some(token)) = tokenizer.next_token() {
+        tokens.push(token);
+    }
+    alias_target(&tokens, page.page_info().platform.as_deref())
+}
+
+/// A page and the alias pages that lead to it.
+#[derive(Debug)]
+pub struct ResolvedPage {
+    /// The names and pages, starting with the requested page and ending with
+    /// the page that is not an alias (or the last page before a cycle or a
+    /// missing page)
+    pub pages: Vec<(String, PageLookupResult)>,
+    /// The name of the page that was found again, if the aliases form a cycle
+    pub cycle: Option<String>,
+}
+
+impl ResolvedPage {
+    /// Return the name and page that the aliases resolve to.
+    pub fn target(&self) -> &(String, PageLookupResult) {
+        // There is always at least the requested page
+        &self.pages[self.pages.len() - 1]
+    }
+}
+
+/// Follow the aliases starting at a page, looking up the targets with `find`.
+///
+/// Resolving stops at a page that is not an alias, at a target that can't be
+/// found and at a page that was seen before. Pages are told apart by their
+/// name and platform, so that e.g. a `windows` page can point to the `common`
+/// page of the same name.
+pub fn resolve_aliases<F>(name: &str, page: PageLookupResult, mut find: F) -> ResolvedPage
+where
+    F: FnMut(&AliasTarget) -> Option<PageLookupResult>,
+{
+    let mut resolved = ResolvedPage {
+        pages: vec![(name.to_string(), page)],
+        cycle: None,
+    };
+    while let Some(target) = page_alias(&resolved.target().1) {
+        let page = match find(&target) {
+            Some(page) => page,
+            None => break,
+        };
+        let seen = resolved.pages.iter().any(|(name, seen)| {
+            *name == target.name && seen.page_info().platform == page.page_info().platform
+        });
+        if seen {
+            resolved.cycle = Some(target.name);
+            break;
+        }
+        resolved.pages.push((target.name, page));
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod test {
+    use super::{page_alias, resolve_aliases, AliasTarget};
+    use crate::cache::PageLookupResult;
+    use crate::source::{MemorySource, SourceChain};
+
+    fn alias(name: &str, target: &str) -> String {
+        format!(
+            "# {}\n\n> This command is an alias of `{} -x`.\n\n\
+             - View documentation for the original command:\n\n`tldr {}`\n",
+            name,
+            target,
+            target.replace('-', " ")
+        )
+    }
+
+    fn chain() -> SourceChain {
+        SourceChain::new().with_source(
+            MemorySource::new()
+                .with_page("en", "common", "egrep", alias("egrep", "grep"))
+                .with_page(
+                    "en",
+                    "common",
+                    "grep",
+                    "# grep\n\n- Search:\n\n`grep {{x}}`\n",
+                )
+                .with_page("en", "common", "gh-cs", alias("gh-cs", "gh-codespace"))
+                .with_page(
+                    "en",
+                    "common",
+                    "gh-codespace",
+                    alias("gh-codespace", "gh-cs"),
+                )
+                .with_page("en", "common", "vi", alias("vi", "vim"))
+                .with_page(
+                    "en",
+                    "common",
+                    "ls",
+                    "# ls\n\n> `ll` is a common alias of `ls -l`.\n\n\
+                     - List files:\n\n`ls`\n\n- List all files:\n\n`ls -a`\n",
+                )
+                .with_page("en", "common", "self", alias("self", "self"))
+                .with_page(
+                    "en",
+                    "common",
+                    "curl",
+                    "# curl\n\n- Download:\n\n`curl {{url}}`\n",
+                )
+                .with_page(
+                    "en",
+                    "windows",
+                    "curl",
+                    "# curl\n\n> In PowerShell, this command may be an alias of \
+                     `Invoke-WebRequest`.\n\n\
+                     - View documentation for the original command:\n\n`tldr curl -p common`\n",
+                )
+                .with_page(
+                    "de",
+                    "common",
+                    "egrep",
+                    "# egrep\n\n> Ein Alias.\n\n- Doku:\n\n`tldr grep`\n",
+                ),
+        )
+    }
+
+    fn find(name: &str) -> Option<PageLookupResult> {
+        chain().find_page(name, Some("windows"), &["en".into()])
+    }
+
+    fn find_target(target: &AliasTarget) -> Option<PageLookupResult> {
+        let platform = target.platform.as_deref().or(Some("windows"));
+        chain().find_page(&target.name, platform, &["en".into()])
+    }
+
+    fn target(name: &str) -> Option<String> {
+        page_alias(&find(name).unwrap()).map(|target| target.name)
+    }
+
+    #[test]
+    fn test_page_alias() {
+        assert_eq!(target("egrep").as_deref(), Some("grep"));
+        assert_eq!(target("grep"), None);
+        assert_eq!(target("ls"), None);
+        assert_eq!(target("self"), None);
+        assert_eq!(
+            page_alias(&find("curl").unwrap()),
+            Some(AliasTarget {
+                name: "curl".into(),
+                platform: Some("common".into()),
+            })
+        );
+        let page = chain().find_page("egrep", None, &["de".into()]).unwrap();
+        assert_eq!(page_alias(&page).unwrap().name, "grep");
+    }
+
+    #[test]
+    fn test_resolve_aliases() {
+        let names = |name: &str| {
+            let resolved = resolve_aliases(name, find(name).unwrap(), find_target);
+            let names: Vec<String> = resolved.pages.iter().map(|(n, _)| n.clone()).collect();
+            (names, resolved.cycle)
+        };
+        assert_eq!(names("egrep"), (vec!["egrep".into(), "grep".into()], None));
+        assert_eq!(names("ls"), (vec!["ls".into()], None));
+
+        let (pages, cycle) = names("gh-cs");
+        assert_eq!(pages.len(), 2);
+        assert_eq!(cycle.as_deref(), Some("gh-cs"));
+
+        // The windows page points to the common page of the same name
+        let resolved = resolve_aliases("curl", find("curl").unwrap(), find_target);
+        assert_eq!(resolved.pages.len(), 2);
+        assert_eq!(resolved.cycle, None);
+        assert_eq!(
+            resolved.target().1.page_info().platform.as_deref(),
+            Some("common")
+        );
+
+        // The target of `vi` doesn't exist
+        assert_eq!(names("vi"), (vec!["vi".into()], None));
+    }
+}
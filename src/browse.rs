@@ -0,0 +1,503 @@
+//! An interactive full-screen browser for the pages in the cache.
+
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::{cursor, queue, terminal};
+
+use tealdeer::TealdeerError::{self, WriteError};
+use tealdeer::{render_page, Config, LineType, PageInfo, Renderer, SourceChain, TerminalRenderer};
+
+use crate::PLATFORMS;
+
+/// The maximum width of the list of pages.
+const MAX_LIST_WIDTH: u16 = 30;
+
+/// A line of a rendered page.
+#[derive(Debug, Clone, PartialEq)]
+struct PageLine {
+    text: String,
+    /// The example command, if the line shows one
+    command: Option<String>,
+}
+
+/// Renders a page as plain text and remembers which lines show an example
+/// command.
+struct LineRenderer {
+    inner: TerminalRenderer,
+    lines: Vec<PageLine>,
+}
+
+impl LineRenderer {
+    /// Collect the lines written by `f`, associated with `command`.
+    fn record<F>(&mut self, command: Option<String>, f: F) -> Result<(), TealdeerError>
+    where
+        F: FnOnce(&mut TerminalRenderer, &mut dyn Write) -> Result<(), TealdeerError>,
+    {
+        let mut buffer = Vec::new();
+        f(&mut self.inner, &mut buffer)?;
+        let text = String::from_utf8_lossy(&buffer);
+        for line in text.lines() {
+            self.lines.push(PageLine {
+                text: line.into(),
+                command: command.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for LineRenderer {
+    fn start(
+        &mut self,
+        _writer: &mut dyn Write,
+        page_info: &PageInfo,
+        patch_name: Option<&str>,
+    ) -> Result<(), TealdeerError> {
+        self.record(None, |inner, buffer| {
+            inner.start(buffer, page_info, patch_name)
+        })
+    }
+
+    fn render(&mut self, _writer: &mut dyn Write, token: LineType) -> Result<(), TealdeerError> {
+        let command = match token {
+            LineType::ExampleCode(ref code) => Some(code.clone()),
+            _ => None,
+        };
+        self.record(command, |inner, buffer| inner.render(buffer, token))
+    }
+
+    fn end(&mut self, _writer: &mut dyn Write) -> Result<(), TealdeerError> {
+        self.record(None, |inner, buffer| inner.end(buffer))
+    }
+}
+
+/// What to do after a key press.
+#[derive(Debug, PartialEq)]
+enum Action {
+    Continue,
+    Quit,
+    /// Quit and print the example command
+    Print(String),
+}
+
+/// The state of the browser, independent of the terminal.
+struct Browser {
+    sources: SourceChain,
+    config: Config,
+    platform: usize,
+    languages: Vec<String>,
+    language: usize,
+    /// All pages of the current platform
+    pages: Vec<String>,
+    /// The pages matching the filter
+    matches: Vec<String>,
+    filter: String,
+    /// The index of the selected page in `matches`
+    selected: usize,
+    /// The selected page, rendered
+    lines: Vec<PageLine>,
+    /// The index of the selected example command in `lines`
+    example: Option<usize>,
+}
+
+impl Browser {
+    fn new(
+        sources: SourceChain,
+        config: &Config,
+        platform: Option<&str>,
+        languages: Vec<String>,
+    ) -> Result<Self, TealdeerError> {
+        let platform = platform
+            .and_then(|platform| PLATFORMS.iter().position(|&p| p == platform))
+            .unwrap_or(PLATFORMS.len() - 1);
+        let mut browser = Self {
+            sources,
+            config: config.clone(),
+            platform,
+            languages,
+            language: 0,
+            pages: Vec::new(),
+            matches: Vec::new(),
+            filter: String::new(),
+            selected: 0,
+            lines: Vec::new(),
+            example: None,
+        };
+        browser.load_pages()?;
+        Ok(browser)
+    }
+
+    fn platform(&self) -> &'static str {
+        PLATFORMS[self.platform]
+    }
+
+    fn language(&self) -> &str {
+        self.languages
+            .get(self.language)
+            .map_or("en", String::as_str)
+    }
+
+    /// Return the selected page.
+    fn page(&self) -> Option<&str> {
+        self.matches.get(self.selected).map(String::as_str)
+    }
+
+    /// List the pages of the current platform.
+    fn load_pages(&mut self) -> Result<(), TealdeerError> {
+        self.pages = self.sources.list_pages(Some(self.platform()))?;
+        self.apply_filter(true);
+        Ok(())
+    }
+
+    /// Update the matching pages. Unless `keep_selection` is set, the best
+    /// match is selected.
+    fn apply_filter(&mut self, keep_selection: bool) {
+        let selected = if keep_selection {
+            self.page().map(String::from)
+        } else {
+            None
+        };
+        let filter = self.filter.to_lowercase();
+        self.matches = self
+            .pages
+            .iter()
+            .filter(|page| page.to_lowercase().contains(&filter))
+            .cloned()
+            .collect();
+        // Prefer pages starting with the filter
+        self.matches.sort_by_key(|page| !page.starts_with(&filter));
+        self.selected = selected
+            .and_then(|selected| self.matches.iter().position(|page| *page == selected))
+            .unwrap_or(0);
+        self.load_page();
+    }
+
+    /// Render the selected page.
+    fn load_page(&mut self) {
+        self.lines.clear();
+        self.example = None;
+        let name = match self.page() {
+            Some(name) => name.to_string(),
+            None => return,
+        };
+        // Fall back to English if there is no translation
+        let mut languages = vec![self.language().to_string()];
+        if languages[0] != "en" {
+            languages.push("en".into());
+        }
+        let page = match self
+            .sources
+            .find_page(&name, Some(self.platform()), &languages)
+        {
+            Some(page) => page,
+            None => return,
+        };
+        let mut renderer = LineRenderer {
+            inner: TerminalRenderer::plain(&self.config),
+            lines: Vec::new(),
+        };
+        self.lines = match render_page(&mut renderer, &mut io::sink(), &page) {
+            Ok(()) => renderer.lines,
+            Err(e) => vec![PageLine {
+                text: e.to_string(),
+                command: None,
+            }],
+        };
+        self.example = self.lines.iter().position(|line| line.command.is_some());
+    }
+
+    /// Select the previous (`forward` is false) or next example command.
+    fn move_example(&mut self, forward: bool) {
+        let current = match self.example {
+            Some(current) => current,
+            None => return,
+        };
+        let is_example = |i: &usize| self.lines[*i].command.is_some();
+        let next = if forward {
+            (current + 1..self.lines.len()).find(is_example)
+        } else {
+            (0..current).rev().find(is_example)
+        };
+        if let Some(next) = next {
+            self.example = Some(next);
+        }
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() as isize - 1;
+        let selected = (self.selected as isize + offset).max(0).min(last);
+        #[allow(clippy::cast_sign_loss)]
+        let selected = selected as usize;
+        if selected != self.selected {
+            self.selected = selected;
+            self.load_page();
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Result<Action, TealdeerError> {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                return Ok(Action::Quit)
+            }
+            (KeyCode::Enter, _) => {
+                if let Some(command) = self.example.and_then(|i| self.lines[i].command.clone()) {
+                    return Ok(Action::Print(command));
+                }
+            }
+            (KeyCode::Up, _) => self.move_selection(-1),
+            (KeyCode::Down, _) => self.move_selection(1),
+            (KeyCode::PageUp, _) => self.move_selection(-10),
+            (KeyCode::PageDown, _) => self.move_selection(10),
+            (KeyCode::Left, _) => self.move_example(false),
+            (KeyCode::Right, _) => self.move_example(true),
+            (KeyCode::Tab, _) => {
+                self.platform = (self.platform + 1) % PLATFORMS.len();
+                self.load_pages()?;
+            }
+            (KeyCode::BackTab, _) if !self.languages.is_empty() => {
+                self.language = (self.language + 1) % self.languages.len();
+                self.load_page();
+            }
+            (KeyCode::Backspace, _) => {
+                self.filter.pop();
+                self.apply_filter(false);
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE) | (KeyCode::Char(c), KeyModifiers::SHIFT) => {
+                self.filter.push(c);
+                self.apply_filter(false);
+            }
+            _ => {}
+        }
+        Ok(Action::Continue)
+    }
+
+    /// Draw the browser to the terminal.
+    fn draw<W: Write>(&self, out: &mut W) -> crossterm::Result<()> {
+        let (width, height) = terminal::size()?;
+        let list_width = (width / 3).min(MAX_LIST_WIDTH);
+        let rows = usize::from(height.saturating_sub(2));
+
+        queue!(
+            out,
+            terminal::Clear(terminal::ClearType::All),
+            cursor::MoveTo(0, 0),
+            SetAttribute(Attribute::Bold),
+            Print(truncate(
+                &format!(
+                    "Search: {}  [platform: {}] [language: {}]",
+                    self.filter,
+                    self.platform(),
+                    self.language()
+                ),
+                usize::from(width)
+            )),
+            SetAttribute(Attribute::Reset),
+        )?;
+
+        // Scroll the list so that the selected page is visible
+        let list_offset = (self.selected + 1).saturating_sub(rows);
+        for (row, page) in self.matches.iter().skip(list_offset).take(rows).enumerate() {
+            let selected = row + list_offset == self.selected;
+            queue!(out, cursor::MoveTo(0, row as u16 + 1))?;
+            if selected {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                out,
+                Print(truncate(page, usize::from(list_width.saturating_sub(1)))),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+
+        // Scroll the page so that the selected example is visible
+        let page_offset = self
+            .example
+            .map_or(0, |example| (example + 2).saturating_sub(rows));
+        let page_width = usize::from(width.saturating_sub(list_width + 1));
+        for (row, line) in self.lines.iter().skip(page_offset).take(rows).enumerate() {
+            queue!(out, cursor::MoveTo(list_width + 1, row as u16 + 1))?;
+            if Some(row + page_offset) == self.example {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                out,
+                Print(truncate(&line.text, page_width)),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            SetAttribute(Attribute::Dim),
+            Print(truncate(
+                "Type to filter, Up/Down: select page, Left/Right: select example, \
+                 Enter: print example, Tab: platform, Shift+Tab: language, Esc: quit",
+                usize::from(width)
+            )),
+            SetAttribute(Attribute::Reset),
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Truncate `text` to at most `width` characters.
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// Run the event loop until the user quits.
+fn run<W: Write>(browser: &mut Browser, out: &mut W) -> crossterm::Result<Action> {
+    loop {
+        browser.draw(out)?;
+        if let Event::Key(key) = event::read()? {
+            let action = browser
+                .handle_key(key)
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            if action != Action::Continue {
+                return Ok(action);
+            }
+        }
+    }
+}
+
+/// The terminal in raw mode on the alternate screen. The terminal is restored
+/// when this is dropped, so that it is also restored if the browser panics.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> crossterm::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let raw_terminal = Self;
+        queue!(io::stderr(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(raw_terminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let mut stderr = io::stderr();
+        let _ = queue!(stderr, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = stderr.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Browse the pages interactively.
+///
+/// The browser is drawn on stderr, so that the selected example command can
+/// be printed to stdout (e.g. to be captured by a shell function).
+pub fn browse(
+    sources: SourceChain,
+    config: &Config,
+    platform: Option<&str>,
+    languages: Vec<String>,
+) -> Result<(), TealdeerError> {
+    let mut browser = Browser::new(sources, config, platform, languages)?;
+
+    let to_io_error = |e: crossterm::ErrorKind| match e {
+        crossterm::ErrorKind::IoError(e) => WriteError(e),
+        e => WriteError(io::Error::other(e.to_string())),
+    };
+    let action = {
+        let _raw_terminal = RawTerminal::enter().map_err(to_io_error)?;
+        run(&mut browser, &mut io::stderr())
+    };
+    // The terminal is restored before the command is printed
+    if let Action::Print(command) = action.map_err(to_io_error)? {
+        println!("{}", command);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Action, Browser};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use tealdeer::{Config, MemorySource, SourceChain};
+
+    fn browser() -> Browser {
+        let sources = SourceChain::new().with_source(
+            MemorySource::new()
+                .with_page("en", "common", "tar", "# tar\n\n> Archiver.\n\n- Create:\n\n`tar cf {{a.tar}}`\n\n- Extract:\n\n`tar xf {{a.tar}}`\n")
+                .with_page("de", "common", "tar", "# tar\n\n> Archivierer.\n\n- Erstellen:\n\n`tar cf {{a.tar}}`\n")
+                .with_page("en", "common", "git", "# git\n\n> VCS.\n\n- Status:\n\n`git status`\n")
+                .with_page("en", "common", "gitk", "# gitk\n\n> Browser.\n\n- Start:\n\n`gitk`\n")
+                .with_page("en", "osx", "brew", "# brew\n\n> Packages.\n\n- Update:\n\n`brew update`\n"),
+        );
+        Browser::new(
+            sources,
+            &Config::default(),
+            Some("linux"),
+            vec!["en".into(), "de".into()],
+        )
+        .unwrap()
+    }
+
+    fn key(browser: &mut Browser, code: KeyCode) -> Action {
+        browser
+            .handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_filter_and_select() {
+        let mut browser = browser();
+        assert_eq!(browser.matches, vec!["git", "gitk", "tar"]);
+        assert_eq!(browser.page(), Some("git"));
+
+        key(&mut browser, KeyCode::Char('K'));
+        assert_eq!(browser.matches, vec!["gitk"]);
+        key(&mut browser, KeyCode::Backspace);
+        key(&mut browser, KeyCode::Char('a'));
+        assert_eq!(browser.matches, vec!["tar"]);
+        key(&mut browser, KeyCode::Backspace);
+        key(&mut browser, KeyCode::Down);
+        key(&mut browser, KeyCode::Down);
+        assert_eq!(browser.page(), Some("tar"));
+    }
+
+    #[test]
+    fn test_examples() {
+        let mut browser = browser();
+        key(&mut browser, KeyCode::Char('t'));
+        assert_eq!(
+            key(&mut browser, KeyCode::Enter),
+            Action::Print("tar cf {{a.tar}}".into())
+        );
+        key(&mut browser, KeyCode::Right);
+        assert_eq!(
+            key(&mut browser, KeyCode::Enter),
+            Action::Print("tar xf {{a.tar}}".into())
+        );
+        key(&mut browser, KeyCode::Left);
+        assert_eq!(
+            key(&mut browser, KeyCode::Enter),
+            Action::Print("tar cf {{a.tar}}".into())
+        );
+        assert_eq!(key(&mut browser, KeyCode::Esc), Action::Quit);
+    }
+
+    #[test]
+    fn test_switch_platform_and_language() {
+        let mut browser = browser();
+        key(&mut browser, KeyCode::Tab);
+        assert_eq!(browser.platform(), "osx");
+        assert_eq!(browser.matches, vec!["brew", "git", "gitk", "tar"]);
+
+        key(&mut browser, KeyCode::Char('t'));
+        key(&mut browser, KeyCode::Char('a'));
+        key(&mut browser, KeyCode::BackTab);
+        assert_eq!(browser.language(), "de");
+        assert!(browser
+            .lines
+            .iter()
+            .any(|line| line.text.contains("Archivierer.")));
+    }
+}
@@ -11,38 +11,81 @@ use log::debug;
 use reqwest::{blocking::Client, Proxy};
 use std::time::{Duration, SystemTime};
 use tar::Archive;
-use walkdir::{DirEntry, WalkDir};
 
+use crate::coverage::TranslationHashes;
 use crate::error::TealdeerError::{self, CacheError, UpdateError};
-use crate::types::{OsType, PathSource};
+use crate::source::{CacheSource, CustomPagesSource, PageContent, PageSource, SourceChain};
+use crate::types::{OsType, PageInfo, PathSource, SourceKind};
 
 #[derive(Debug)]
 pub struct Cache {
     url: String,
     os: OsType,
+    /// The sources to look pages up in, in order
+    source_order: Vec<SourceKind>,
 }
 
 #[derive(Debug)]
 pub struct PageLookupResult {
-    page_path: PathBuf,
-    patch_path: Option<PathBuf>,
+    page: PageContent,
+    patch: Option<PageContent>,
+    info: PageInfo,
 }
 
 impl PageLookupResult {
+    /// Create a lookup result for a page file. The platform and language
+    /// are derived from its location in the cache.
     pub fn with_page(page_path: PathBuf) -> Self {
+        let info = page_info_from_path(&page_path);
+        Self::with_content(PageContent::File(page_path), info)
+    }
+
+    pub fn with_content(page: PageContent, info: PageInfo) -> Self {
         Self {
-            page_path,
-            patch_path: None,
+            page,
+            patch: None,
+            info,
         }
     }
 
-    pub fn with_optional_patch(mut self, patch_path: Option<PathBuf>) -> Self {
-        self.patch_path = patch_path;
+    pub fn with_optional_patch(mut self, patch: Option<PageContent>) -> Self {
+        self.patch = patch;
         self
     }
 
-    pub fn paths(&self) -> impl Iterator<Item = &Path> {
-        iter::once(self.page_path.as_path()).chain(self.patch_path.as_deref().into_iter())
+    /// Return the page followed by its patch, if there is one.
+    pub fn contents(&self) -> impl Iterator<Item = &PageContent> {
+        iter::once(&self.page).chain(self.patch.iter())
+    }
+
+    /// Return the platform and language of the page.
+    pub fn page_info(&self) -> &PageInfo {
+        &self.info
+    }
+}
+
+/// Return the platform and language of a page, based on its location
+/// in the cache (`pages.<lang>/<platform>/<name>.md`).
+fn page_info_from_path(page_path: &Path) -> PageInfo {
+    let platform_dir = page_path.parent();
+    let language = platform_dir
+        .and_then(Path::parent)
+        .and_then(Path::file_name)
+        .and_then(OsStr::to_str)
+        .and_then(|lang_dir| match lang_dir {
+            "pages" => Some("en"),
+            _ => lang_dir.strip_prefix("pages."),
+        });
+    match language {
+        Some(language) => PageInfo {
+            platform: platform_dir
+                .and_then(Path::file_name)
+                .and_then(OsStr::to_str)
+                .map(String::from),
+            language: Some(language.into()),
+            custom: false,
+        },
+        None => PageInfo::default(),
     }
 }
 
@@ -54,9 +97,17 @@ impl Cache {
         Self {
             url: url.into(),
             os,
+            source_order: vec![SourceKind::Custom, SourceKind::Cache],
         }
     }
 
+    /// Set the sources to look pages up in, in order. By default, custom
+    /// pages take precedence over the pages in the cache.
+    pub fn with_source_order(mut self, source_order: Vec<SourceKind>) -> Self {
+        self.source_order = source_order;
+        self
+    }
+
     /// Return the path to the cache directory.
     pub fn get_cache_dir() -> Result<(PathBuf, PathSource), TealdeerError> {
         // Allow overriding the cache directory by setting the
@@ -71,14 +122,16 @@ impl Cache {
                 "Path specified by $TEALDEER_CACHE_DIR \
                      does not exist or is not a directory."
                     .into(),
+                None,
             ));
         };
 
         // Otherwise, fall back to user cache directory.
         match get_app_root(AppDataType::UserCache, &crate::APP_INFO) {
             Ok(dirs) => Ok((dirs, PathSource::OsConvention)),
-            Err(_) => Err(CacheError(
+            Err(e) => Err(CacheError(
                 "Could not determine user cache directory.".into(),
+                Some(e.into()),
             )),
         }
     }
@@ -120,10 +173,18 @@ impl Cache {
         // Determine paths
         let (cache_dir, _) = Self::get_cache_dir()?;
 
+        // Keep the hashes recorded by the previous update, a broken file only
+        // loses the outdated translations detected so far
+        let hashes = TranslationHashes::load(&Self::translation_hashes_path(&cache_dir))
+            .unwrap_or_else(|e| {
+                debug!("Ignoring the previous translation hashes: {}", e);
+                TranslationHashes::default()
+            });
+
         // Make sure that cache directory exists
         debug!("Ensure cache directory {:?} exists", &cache_dir);
         fs::create_dir_all(&cache_dir)
-            .map_err(|e| UpdateError(format!("Could not create cache directory: {}", e)))?;
+            .map_err(|e| UpdateError("Could not create cache directory".into(), Some(e.into())))?;
 
         // Clear cache directory
         // Note: This is not the best solution. Ideally we would download the
@@ -136,9 +197,23 @@ impl Cache {
         // Extract archive
         archive
             .unpack(&cache_dir)
-            .map_err(|e| UpdateError(format!("Could not unpack compressed data: {}", e)))?;
+            .map_err(|e| UpdateError("Could not unpack compressed data".into(), Some(e.into())))?;
 
-        Ok(())
+        // Record the upstream hashes of the translations
+        hashes
+            .updated(&Self::source()?)?
+            .save(&Self::translation_hashes_path(&cache_dir))
+    }
+
+    /// Return the path of the translation hashes in the cache directory.
+    fn translation_hashes_path(cache_dir: &Path) -> PathBuf {
+        cache_dir.join("translations.json")
+    }
+
+    /// Return the translation hashes recorded by the last update.
+    pub fn translation_hashes() -> Result<TranslationHashes, TealdeerError> {
+        let (cache_dir, _) = Self::get_cache_dir()?;
+        TranslationHashes::load(&Self::translation_hashes_path(&cache_dir))
     }
 
     /// Return the duration since the cache directory was last modified.
@@ -155,7 +230,7 @@ impl Cache {
     }
 
     /// Return the platform directory.
-    fn get_platform_dir(&self) -> Option<&'static str> {
+    pub fn get_platform_dir(&self) -> Option<&'static str> {
         match self.os {
             OsType::Linux => Some("linux"),
             OsType::OsX => Some("osx"),
@@ -165,24 +240,24 @@ impl Cache {
         }
     }
 
-    /// Check for pages for a given platform in one of the given languages.
-    fn find_page_for_platform(
-        page_name: &str,
-        cache_dir: &Path,
-        platform: &str,
-        language_dirs: &[String],
-    ) -> Option<PathBuf> {
-        language_dirs
-            .iter()
-            .map(|lang_dir| cache_dir.join(lang_dir).join(platform).join(page_name))
-            .find(|path| path.exists() && path.is_file())
+    /// Return the source for the extracted pages in the cache directory.
+    pub fn source() -> Result<CacheSource, TealdeerError> {
+        let (cache_dir, _) = Self::get_cache_dir()?;
+        Ok(CacheSource::new(cache_dir.join("tldr-master")))
     }
 
-    /// Look up custom patch (<name>.patch). If it exists, store it in a variable.
-    fn find_patch(patch_name: &str, custom_pages_dir: Option<&Path>) -> Option<PathBuf> {
-        custom_pages_dir
-            .map(|custom_dir| custom_dir.join(patch_name))
-            .filter(|path| path.exists() && path.is_file())
+    /// Return the lookup chain with the configured sources. The custom pages
+    /// are skipped if there is no custom pages directory.
+    pub fn sources(&self, custom_pages_dir: Option<&Path>) -> Result<SourceChain, TealdeerError> {
+        let mut chain = SourceChain::new();
+        for kind in &self.source_order {
+            chain = match (kind, custom_pages_dir) {
+                (SourceKind::Custom, Some(dir)) => chain.with_source(CustomPagesSource::new(dir)),
+                (SourceKind::Custom, None) => chain,
+                (SourceKind::Cache, _) => chain.with_source(Self::source()?),
+            };
+        }
+        Ok(chain)
     }
 
     /// Search for a page and return the path to it.
@@ -192,124 +267,64 @@ impl Cache {
         languages: &[String],
         custom_pages_dir: Option<&Path>,
     ) -> Option<PageLookupResult> {
-        let page_filename = format!("{}.md", name);
-        let patch_filename = format!("{}.patch", name);
-        let custom_filename = format!("{}.page", name);
-
-        // Get cache dir
-        let cache_dir = match Self::get_cache_dir() {
-            Ok((cache_dir, _)) => cache_dir.join("tldr-master"),
+        match self.sources(custom_pages_dir) {
+            Ok(sources) => sources.find_page(name, self.get_platform_dir(), languages),
             Err(e) => {
                 log::error!("Could not get cache directory: {}", e);
-                return None;
+                None
             }
-        };
+        }
+    }
 
-        let lang_dirs: Vec<String> = languages
+    /// Search for the translations of a page in the given languages, in
+    /// order. Languages without a translation are skipped, and so are custom
+    /// pages because they don't have a language.
+    pub fn find_translations(
+        &self,
+        name: &str,
+        languages: &[String],
+        custom_pages_dir: Option<&Path>,
+    ) -> Result<Vec<PageLookupResult>, TealdeerError> {
+        let sources = self.sources(custom_pages_dir)?;
+        Ok(languages
             .iter()
-            .map(|lang| {
-                if lang == "en" {
-                    String::from("pages")
-                } else {
-                    format!("pages.{}", lang)
-                }
+            .filter_map(|language| {
+                sources
+                    .find_page(
+                        name,
+                        self.get_platform_dir(),
+                        std::slice::from_ref(language),
+                    )
+                    .filter(|page| page.page_info().language.as_ref() == Some(language))
             })
-            .collect();
-
-        // Look up custom page (<name>.page). If it exists, return it directly
-        if let Some(config_dir) = custom_pages_dir {
-            let custom_page = config_dir.join(custom_filename);
-            if custom_page.exists() && custom_page.is_file() {
-                return Some(PageLookupResult::with_page(custom_page));
-            }
-        }
-
-        let patch_path = Self::find_patch(&patch_filename, custom_pages_dir.as_deref());
-
-        // Try to find a platform specific path next, append custom patch to it.
-        if let Some(pf) = self.get_platform_dir() {
-            if let Some(page) =
-                Self::find_page_for_platform(&page_filename, &cache_dir, pf, &lang_dirs)
-            {
-                return Some(PageLookupResult::with_page(page).with_optional_patch(patch_path));
-            }
-        }
-
-        // Did not find platform specific results, fall back to "common"
-        Self::find_page_for_platform(&page_filename, &cache_dir, "common", &lang_dirs)
-            .map(|page| PageLookupResult::with_page(page).with_optional_patch(patch_path))
+            .collect())
     }
 
     /// Return the available pages.
     pub fn list_pages(&self) -> Result<Vec<String>, TealdeerError> {
-        // Determine platforms directory and platform
-        let (cache_dir, _) = Self::get_cache_dir()?;
-        let platforms_dir = cache_dir.join("tldr-master").join("pages");
-        let platform_dir = self.get_platform_dir();
-
-        // Closure that allows the WalkDir instance to traverse platform
-        // specific and common page directories, but not others.
-        let should_walk = |entry: &DirEntry| -> bool {
-            let file_type = entry.file_type();
-            let file_name = match entry.file_name().to_str() {
-                Some(name) => name,
-                None => return false,
-            };
-            if file_type.is_dir() {
-                if file_name == "common" {
-                    return true;
-                }
-                if let Some(platform) = platform_dir {
-                    return file_name == platform;
-                }
-            } else if file_type.is_file() {
-                return true;
-            }
-            false
-        };
-
-        // Recursively walk through common and (if applicable) platform specific directory
-        let mut pages = WalkDir::new(platforms_dir)
-            .min_depth(1) // Skip root directory
-            .into_iter()
-            .filter_entry(|e| should_walk(e)) // Filter out pages for other architectures
-            .filter_map(Result::ok) // Convert results to options, filter out errors
-            .filter_map(|e| {
-                let path = e.path();
-                let extension = &path.extension().and_then(OsStr::to_str).unwrap_or("");
-                if e.file_type().is_file() && extension == &"md" {
-                    path.file_stem()
-                        .and_then(|stem| stem.to_str().map(|s| s.into()))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<String>>();
-        pages.sort();
-        pages.dedup();
-        Ok(pages)
+        Self::source()?.list(self.get_platform_dir())
     }
 
     /// Delete the cache directory.
     pub fn clear() -> Result<(), TealdeerError> {
         let (path, _) = Self::get_cache_dir()?;
         if path.exists() && path.is_dir() {
-            fs::remove_dir_all(&path).map_err(|_| {
-                CacheError(format!(
-                    "Could not remove cache directory ({}).",
-                    path.display()
-                ))
+            fs::remove_dir_all(&path).map_err(|e| {
+                CacheError(
+                    format!("Could not remove cache directory ({})", path.display()),
+                    Some(e.into()),
+                )
             })?;
         } else if path.exists() {
-            return Err(CacheError(format!(
-                "Cache path ({}) is not a directory.",
-                path.display()
-            )));
+            return Err(CacheError(
+                format!("Cache path ({}) is not a directory.", path.display()),
+                None,
+            ));
         } else {
-            return Err(CacheError(format!(
-                "Cache path ({}) does not exist.",
-                path.display()
-            )));
+            return Err(CacheError(
+                format!("Cache path ({}) does not exist.", path.display()),
+                None,
+            ));
         };
         Ok(())
     }
@@ -323,18 +338,35 @@ mod tests {
     #[test]
     fn test_page_lookup_result_iter_with_patch() {
         let lookup = PageLookupResult::with_page(PathBuf::from("test.page"))
-            .with_optional_patch(Some(PathBuf::from("test.patch")));
-        let mut iter = lookup.paths();
-        assert_eq!(iter.next(), Some(Path::new("test.page")));
-        assert_eq!(iter.next(), Some(Path::new("test.patch")));
+            .with_optional_patch(Some(PageContent::File(PathBuf::from("test.patch"))));
+        let mut iter = lookup.contents();
+        assert_eq!(iter.next(), Some(&PageContent::File("test.page".into())));
+        assert_eq!(iter.next(), Some(&PageContent::File("test.patch".into())));
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_page_lookup_result_page_info() {
+        let lookup =
+            PageLookupResult::with_page(PathBuf::from("tldr-master/pages.de/linux/tar.md"));
+        let info = lookup.page_info();
+        assert_eq!(info.platform.as_deref(), Some("linux"));
+        assert_eq!(info.language.as_deref(), Some("de"));
+
+        let lookup = PageLookupResult::with_page(PathBuf::from("tldr-master/pages/common/tar.md"));
+        let info = lookup.page_info();
+        assert_eq!(info.platform.as_deref(), Some("common"));
+        assert_eq!(info.language.as_deref(), Some("en"));
+
+        let lookup = PageLookupResult::with_page(PathBuf::from("custom/tar.page"));
+        assert_eq!(lookup.page_info(), &PageInfo::default());
+    }
+
     #[test]
     fn test_page_lookup_result_iter_no_patch() {
         let lookup = PageLookupResult::with_page(PathBuf::from("test.page"));
-        let mut iter = lookup.paths();
-        assert_eq!(iter.next(), Some(Path::new("test.page")));
+        let mut iter = lookup.contents();
+        assert_eq!(iter.next(), Some(&PageContent::File("test.page".into())));
         assert_eq!(iter.next(), None);
     }
 }
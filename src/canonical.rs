@@ -0,0 +1,215 @@
+//! Code to rewrite a page in the canonical markdown format.
+
+use std::io::BufRead;
+
+use crate::error::TealdeerError::{self, ParseError};
+use crate::tokenizer::{TldrFormat, Tokenizer};
+use crate::types::LineType;
+
+/// The block of the page that was written last.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+enum Block {
+    Start,
+    Title,
+    Description,
+    ExampleText,
+    ExampleCode,
+    Other,
+}
+
+/// Abbreviations whose period must be kept when an example text ends with them.
+const ABBREVIATIONS: &[&str] = &["etc.", "e.g.", "i.e.", "vs.", "approx.", "incl."];
+
+/// Return the example text, ending with a colon.
+///
+/// A trailing sentence period is replaced, but the period of an abbreviation
+/// or an ellipsis is kept.
+fn with_colon(text: &str) -> String {
+    let text = text.trim_end_matches(|c: char| c == ':' || c.is_whitespace());
+    let last_word = text.rsplit(char::is_whitespace).next().unwrap_or(text);
+    let keep_period = text.ends_with("...")
+        || ABBREVIATIONS
+            .iter()
+            .any(|abbreviation| last_word.eq_ignore_ascii_case(abbreviation));
+    if keep_period {
+        format!("{}:", text)
+    } else {
+        format!("{}:", text.trim_end_matches('.').trim_end())
+    }
+}
+
+/// Wrap example code in backticks.
+fn with_backticks(code: &str) -> String {
+    format!("`{}`", code.trim())
+}
+
+/// Wrap a code line without proper backticks in backticks, dropping a stray
+/// delimiting backtick on either side.
+fn with_stray_backticks(code: &str) -> String {
+    let code = code.trim();
+    let code = code.strip_prefix('`').unwrap_or(code);
+    with_backticks(code.strip_suffix('`').unwrap_or(code))
+}
+
+/// Rewrite a page in the canonical format.
+///
+/// The canonical format consists of a `# title` line, `> description` lines,
+/// and examples consisting of a `- text:` line ending with a colon and a
+/// code line wrapped in backticks, all separated by single blank lines.
+pub fn canonicalize<R>(tokenizer: &mut Tokenizer<R>) -> Result<String, TealdeerError>
+where
+    R: BufRead,
+{
+    let mut output = String::new();
+    let mut last = Block::Start;
+    while let Some(token) = tokenizer.next_token()? {
+        let (block, line) = match token {
+            LineType::Empty => continue,
+            LineType::Title(title) => (Block::Title, format!("# {}", title)),
+            LineType::Description(text) => (Block::Description, format!("> {}", text)),
+            LineType::ExampleText(text) => (Block::ExampleText, format!("- {}", with_colon(&text))),
+            LineType::ExampleCode(code) => (Block::ExampleCode, with_backticks(&code)),
+            // Unknown lines following an example text are code without backticks
+            LineType::Other(text) if last == Block::ExampleText => {
+                (Block::ExampleCode, with_stray_backticks(&text))
+            }
+            LineType::Other(text) => (Block::Other, text),
+        };
+
+        // Description lines are grouped, all other blocks are separated by a blank line
+        let grouped = block == Block::Description && last == Block::Description;
+        if last != Block::Start && !grouped {
+            output.push('\n');
+        }
+        output.push_str(&line);
+        output.push('\n');
+        last = block;
+    }
+    Ok(output)
+}
+
+/// Convert a page in the legacy `V2` format (underlined title, indented code)
+/// to the canonical markdown format.
+///
+/// Returns an error if the page is not in the `V2` format.
+pub fn convert_v2<R>(tokenizer: &mut Tokenizer<R>) -> Result<String, TealdeerError>
+where
+    R: BufRead,
+{
+    let page = canonicalize(tokenizer)?;
+    if *tokenizer.format() == TldrFormat::V2 {
+        Ok(page)
+    } else {
+        Err(ParseError(
+            "The page is not in the legacy format".into(),
+            None,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{canonicalize, convert_v2, with_colon};
+    use crate::tokenizer::Tokenizer;
+
+    fn format(input: &str) -> String {
+        canonicalize(&mut Tokenizer::new(input.as_bytes())).unwrap()
+    }
+
+    const CANONICAL: &str = "# tar\n\n\
+        > Archiving utility.\n\
+        > More information: <https://www.gnu.org/software/tar>.\n\n\
+        - Create an archive from files:\n\n\
+        `tar cf {{target.tar}} {{file1 file2 file3}}`\n\n\
+        - Extract an archive:\n\n\
+        `tar xf {{source.tar}}`\n";
+
+    #[test]
+    fn test_canonical_is_unchanged() {
+        assert_eq!(format(CANONICAL), CANONICAL);
+    }
+
+    #[test]
+    fn test_blank_lines_and_whitespace() {
+        let input = "\
+            #   tar  \n\
+            >Archiving utility.\n\n\
+            >   More information: <https://www.gnu.org/software/tar>.\n\
+            - Create an archive from files:\n\
+            `tar cf {{target.tar}} {{file1 file2 file3}}`\n\n\n\n\
+            -   Extract an archive:\n\n\
+            `tar xf {{source.tar}}`\n\n\n";
+        assert_eq!(format(input), CANONICAL);
+    }
+
+    #[test]
+    fn test_colons_and_backticks() {
+        let input = "\
+            # tar\n\n\
+            > Archiving utility.\n\
+            > More information: <https://www.gnu.org/software/tar>.\n\n\
+            - Create an archive from files\n\n\
+            `tar cf {{target.tar}} {{file1 file2 file3}}\n\n\
+            - Extract an archive.\n\n\
+            tar xf {{source.tar}}\n";
+        assert_eq!(format(input), CANONICAL);
+    }
+
+    #[test]
+    fn test_colon_after_abbreviation() {
+        assert_eq!(
+            with_colon("List files, folders, etc."),
+            "List files, folders, etc.:"
+        );
+        assert_eq!(
+            with_colon("Use a tool (e.g. curl)."),
+            "Use a tool (e.g. curl):"
+        );
+        assert_eq!(with_colon("Wait for it..."), "Wait for it...:");
+        assert_eq!(with_colon("Extract an archive. :"), "Extract an archive:");
+    }
+
+    #[test]
+    fn test_old_format() {
+        let input = "\
+            tar\n\
+            ===\n\n\
+            > Archiving utility.\n\
+            > More information: <https://www.gnu.org/software/tar>.\n\n\
+            Create an archive from files\n\n    \
+            tar cf {{target.tar}} {{file1 file2 file3}}\n\n\
+            Extract an archive\n\n    \
+            tar xf {{source.tar}}\n";
+        assert_eq!(format(input), CANONICAL);
+    }
+
+    #[test]
+    fn test_code_ending_in_backtick() {
+        let page = "# date\n\n- Print the date:\n\n`echo $(date) `date``\n";
+        assert_eq!(format(page), page);
+        assert_eq!(format(&format(page)), page);
+
+        let v2 = "date\n====\n\nPrint the date:\n\n    echo `date`\n";
+        let converted = convert_v2(&mut Tokenizer::new(v2.as_bytes())).unwrap();
+        assert_eq!(converted, "# date\n\n- Print the date:\n\n`echo `date``\n");
+        assert_eq!(format(&converted), converted);
+    }
+
+    #[test]
+    fn test_convert_v2() {
+        let input = "\
+            tar\n\
+            ===\n\n\
+            > Archiving utility.\n\
+            > More information: <https://www.gnu.org/software/tar>.\n\n\
+            Create an archive from files:\n\n    \
+            tar cf {{target.tar}} {{file1 file2 file3}}\n\n\
+            Extract an archive:\n\n    \
+            tar xf {{source.tar}}\n";
+        assert_eq!(
+            convert_v2(&mut Tokenizer::new(input.as_bytes())).unwrap(),
+            CANONICAL
+        );
+        assert!(convert_v2(&mut Tokenizer::new(CANONICAL.as_bytes())).is_err());
+    }
+}
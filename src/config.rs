@@ -10,9 +10,11 @@ use log::debug;
 use serde_derive::{Deserialize, Serialize};
 
 use crate::error::TealdeerError::{self, ConfigError};
-use crate::types::PathSource;
+use crate::types::{ColorDepth, PathSource, SourceKind};
 
 pub const CONFIG_FILE_NAME: &str = "config.toml";
+pub const THEMES_DIR_NAME: &str = "themes";
+const DEFAULT_THEME: &str = "default";
 pub const MAX_CACHE_AGE: Duration = Duration::from_secs(2_592_000); // 30 days
 const DEFAULT_UPDATE_INTERVAL_HOURS: u64 = MAX_CACHE_AGE.as_secs() / 3600; // 30 days
 
@@ -24,6 +26,10 @@ fn default_bold() -> bool {
     false
 }
 
+fn default_italic() -> bool {
+    false
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum RawColor {
@@ -56,7 +62,105 @@ impl From<RawColor> for Color {
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// The RGB values of the 16 basic colors, as used by xterm.
+const BASIC_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// The channel intensities of the 6x6x6 color cube in the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Return the squared distance between two RGB colors.
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a, b| u32::from(u8::abs_diff(a, b)).pow(2);
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Return the RGB values of a color in the 256 color palette.
+fn ansi_to_rgb(num: u8) -> (u8, u8, u8) {
+    match num {
+        0..=15 => BASIC_PALETTE[usize::from(num)],
+        16..=231 => {
+            let index = num - 16;
+            (
+                CUBE_LEVELS[usize::from(index / 36)],
+                CUBE_LEVELS[usize::from(index / 6 % 6)],
+                CUBE_LEVELS[usize::from(index % 6)],
+            )
+        }
+        232..=255 => {
+            let level = 8 + (num - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+/// Return the color of the 256 color palette that is closest to an RGB color.
+fn rgb_to_ansi((r, g, b): (u8, u8, u8)) -> u8 {
+    let nearest_level = |value: u8| {
+        (0..6)
+            .min_by_key(|&i| CUBE_LEVELS[usize::from(i)].abs_diff(value))
+            .unwrap()
+    };
+    let cube = 16 + 36 * nearest_level(r) + 6 * nearest_level(g) + nearest_level(b);
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+    if distance(ansi_to_rgb(gray), (r, g, b)) < distance(ansi_to_rgb(cube), (r, g, b)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Return the basic color that is closest to an RGB color.
+///
+/// Bright colors are mapped to their regular counterparts, because they
+/// can't be expressed by all terminals. Bright black (gray) is mapped to
+/// white, so that it stays visible on dark backgrounds.
+fn rgb_to_basic(rgb: (u8, u8, u8)) -> RawColor {
+    let index = (0..BASIC_PALETTE.len())
+        .min_by_key(|&i| distance(BASIC_PALETTE[i], rgb))
+        .unwrap();
+    match index {
+        0 => RawColor::Black,
+        1 | 9 => RawColor::Red,
+        2 | 10 => RawColor::Green,
+        3 | 11 => RawColor::Yellow,
+        4 | 12 => RawColor::Blue,
+        5 | 13 => RawColor::Purple,
+        6 | 14 => RawColor::Cyan,
+        _ => RawColor::White,
+    }
+}
+
+impl RawColor {
+    /// Map the color to the nearest color the terminal can display.
+    fn downsample(self, depth: ColorDepth) -> Self {
+        match (self, depth) {
+            (Self::Rgb { r, g, b }, ColorDepth::Ansi256) => Self::Ansi(rgb_to_ansi((r, g, b))),
+            (Self::Rgb { r, g, b }, ColorDepth::Ansi16) => rgb_to_basic((r, g, b)),
+            (Self::Ansi(num), ColorDepth::Ansi16) => rgb_to_basic(ansi_to_rgb(num)),
+            (color, _) => color,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 struct RawStyle {
     pub foreground: Option<RawColor>,
     pub background: Option<RawColor>,
@@ -64,18 +168,20 @@ struct RawStyle {
     pub underline: bool,
     #[serde(default = "default_bold")]
     pub bold: bool,
+    #[serde(default = "default_italic")]
+    pub italic: bool,
 }
 
-impl Default for RawStyle {
-    fn default() -> Self {
+impl RawStyle {
+    /// Map the colors to the nearest colors the terminal can display.
+    fn downsample(self, depth: ColorDepth) -> Self {
         Self {
-            foreground: None,
-            background: None,
-            underline: false,
-            bold: false,
+            foreground: self.foreground.map(|color| color.downsample(depth)),
+            background: self.background.map(|color| color.downsample(depth)),
+            ..self
         }
     }
-} // impl RawStyle
+}
 
 impl From<RawStyle> for Style {
     fn from(raw_style: RawStyle) -> Self {
@@ -97,30 +203,128 @@ impl From<RawStyle> for Style {
             style = style.bold();
         }
 
+        if raw_style.italic {
+            style = style.italic();
+        }
+
         style
     }
 }
 
+/// The style section of the config file, as well as the contents of a theme.
+///
+/// Styles that are not set fall back to the theme's style.
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 struct RawStyleConfig {
-    #[serde(default)]
-    pub description: RawStyle,
-    #[serde(default)]
-    pub command_name: RawStyle,
-    #[serde(default)]
-    pub example_text: RawStyle,
-    #[serde(default)]
-    pub example_code: RawStyle,
-    #[serde(default)]
-    pub example_variable: RawStyle,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_name: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_text: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_code: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_variable: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_flag: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_string: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_operator: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_env_var: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub example_subcommand: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_code: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_link: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_emphasis: Option<RawStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<RawStyle>,
 }
 
+impl RawStyleConfig {
+    /// Layer these styles on top of the styles of a theme.
+    fn layered_over(self, theme: &Self) -> Self {
+        Self {
+            theme: self.theme,
+            title: self.title.or(theme.title),
+            description: self.description.or(theme.description),
+            command_name: self.command_name.or(theme.command_name),
+            example_text: self.example_text.or(theme.example_text),
+            example_code: self.example_code.or(theme.example_code),
+            example_variable: self.example_variable.or(theme.example_variable),
+            example_flag: self.example_flag.or(theme.example_flag),
+            example_string: self.example_string.or(theme.example_string),
+            example_operator: self.example_operator.or(theme.example_operator),
+            example_env_var: self.example_env_var.or(theme.example_env_var),
+            example_subcommand: self.example_subcommand.or(theme.example_subcommand),
+            inline_code: self.inline_code.or(theme.inline_code),
+            inline_link: self.inline_link.or(theme.inline_link),
+            inline_emphasis: self.inline_emphasis.or(theme.inline_emphasis),
+            highlight: self.highlight.or(theme.highlight),
+        }
+    }
+
+    /// Map all colors to the nearest colors the terminal can display.
+    fn downsample(self, depth: ColorDepth) -> Self {
+        let downsample = |style: Option<RawStyle>| style.map(|style| style.downsample(depth));
+        Self {
+            theme: self.theme,
+            title: downsample(self.title),
+            description: downsample(self.description),
+            command_name: downsample(self.command_name),
+            example_text: downsample(self.example_text),
+            example_code: downsample(self.example_code),
+            example_variable: downsample(self.example_variable),
+            example_flag: downsample(self.example_flag),
+            example_string: downsample(self.example_string),
+            example_operator: downsample(self.example_operator),
+            example_env_var: downsample(self.example_env_var),
+            example_subcommand: downsample(self.example_subcommand),
+            inline_code: downsample(self.inline_code),
+            inline_link: downsample(self.inline_link),
+            inline_emphasis: downsample(self.inline_emphasis),
+            highlight: downsample(self.highlight),
+        }
+    }
+}
+
+/// How to show alias pages, i.e. pages that only point to another page.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AliasMode {
+    /// Show the page the alias points to, with a note
+    #[default]
+    Follow,
+    /// Show the alias page followed by the page it points to
+    Both,
+    /// Only show the alias page
+    Stub,
+}
+
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 struct RawDisplayConfig {
     #[serde(default)]
     pub compact: bool,
     #[serde(default)]
     pub use_pager: bool,
+    #[serde(default)]
+    pub show_title: bool,
+    #[serde(default)]
+    pub show_patch_separator: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    #[serde(default)]
+    pub aliases: AliasMode,
 }
 
 /// Serde doesn't support default values yet (tracking issue:
@@ -148,10 +352,16 @@ impl Default for RawUpdatesConfig {
     }
 }
 
+fn default_sources() -> Vec<SourceKind> {
+    vec![SourceKind::Custom, SourceKind::Cache]
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 struct RawDirectoriesConfig {
     #[serde(default)]
     pub custom_pages_dir: Option<PathBuf>,
+    #[serde(default = "default_sources")]
+    pub sources: Vec<SourceKind>,
 }
 
 impl Default for RawDirectoriesConfig {
@@ -160,6 +370,7 @@ impl Default for RawDirectoriesConfig {
             custom_pages_dir: get_app_root(AppDataType::UserData, &crate::APP_INFO)
                 .map(|path| path.join("pages"))
                 .ok(),
+            sources: default_sources(),
         }
     }
 }
@@ -181,29 +392,42 @@ impl RawConfig {
         let mut raw_config = Self::default();
 
         // Set default config
-        raw_config.style.example_text.foreground = Some(RawColor::Green);
-        raw_config.style.command_name.foreground = Some(RawColor::Cyan);
-        raw_config.style.example_code.foreground = Some(RawColor::Cyan);
-        raw_config.style.example_variable.foreground = Some(RawColor::Cyan);
-        raw_config.style.example_variable.underline = true;
+        raw_config.style.theme = Some(DEFAULT_THEME.into());
 
         raw_config
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct StyleConfig {
+    pub title: Style,
     pub description: Style,
     pub command_name: Style,
     pub example_text: Style,
     pub example_code: Style,
     pub example_variable: Style,
+    pub example_flag: Style,
+    pub example_string: Style,
+    pub example_operator: Style,
+    pub example_env_var: Style,
+    pub example_subcommand: Style,
+    pub inline_code: Style,
+    pub inline_link: Style,
+    pub inline_emphasis: Style,
+    pub highlight: Style,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DisplayConfig {
     pub compact: bool,
     pub use_pager: bool,
+    pub show_title: bool,
+    pub show_patch_separator: bool,
+    /// The preferred languages of the pages, in order (empty to use the
+    /// language settings of the environment)
+    pub languages: Vec<String>,
+    pub aliases: AliasMode,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -215,6 +439,8 @@ pub struct UpdatesConfig {
 #[derive(Clone, Debug, PartialEq)]
 pub struct DirectoriesConfig {
     pub custom_pages_dir: Option<PathBuf>,
+    /// The sources to look pages up in, in order
+    pub sources: Vec<SourceKind>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -225,19 +451,44 @@ pub struct Config {
     pub directories: DirectoriesConfig,
 }
 
+/// The default settings, without any styles or theme.
+impl Default for Config {
+    fn default() -> Self {
+        RawConfig::default().into()
+    }
+}
+
 impl From<RawConfig> for Config {
     fn from(raw_config: RawConfig) -> Self {
         Self {
             style: StyleConfig {
-                command_name: raw_config.style.command_name.into(),
-                description: raw_config.style.description.into(),
-                example_text: raw_config.style.example_text.into(),
-                example_code: raw_config.style.example_code.into(),
-                example_variable: raw_config.style.example_variable.into(),
+                command_name: raw_config.style.command_name.unwrap_or_default().into(),
+                title: raw_config.style.title.unwrap_or_default().into(),
+                description: raw_config.style.description.unwrap_or_default().into(),
+                example_text: raw_config.style.example_text.unwrap_or_default().into(),
+                example_code: raw_config.style.example_code.unwrap_or_default().into(),
+                example_variable: raw_config.style.example_variable.unwrap_or_default().into(),
+                example_flag: raw_config.style.example_flag.unwrap_or_default().into(),
+                example_string: raw_config.style.example_string.unwrap_or_default().into(),
+                example_operator: raw_config.style.example_operator.unwrap_or_default().into(),
+                example_env_var: raw_config.style.example_env_var.unwrap_or_default().into(),
+                example_subcommand: raw_config
+                    .style
+                    .example_subcommand
+                    .unwrap_or_default()
+                    .into(),
+                inline_code: raw_config.style.inline_code.unwrap_or_default().into(),
+                inline_link: raw_config.style.inline_link.unwrap_or_default().into(),
+                inline_emphasis: raw_config.style.inline_emphasis.unwrap_or_default().into(),
+                highlight: raw_config.style.highlight.unwrap_or_default().into(),
             },
             display: DisplayConfig {
                 compact: raw_config.display.compact,
                 use_pager: raw_config.display.use_pager,
+                show_title: raw_config.display.show_title,
+                show_patch_separator: raw_config.display.show_patch_separator,
+                languages: raw_config.display.languages,
+                aliases: raw_config.display.aliases,
             },
             updates: UpdatesConfig {
                 auto_update: raw_config.updates.auto_update,
@@ -247,50 +498,106 @@ impl From<RawConfig> for Config {
             },
             directories: DirectoriesConfig {
                 custom_pages_dir: raw_config.directories.custom_pages_dir,
+                sources: raw_config.directories.sources,
             },
         }
     }
 }
 
-#[allow(clippy::needless_pass_by_value)]
+/// Themes that are shipped with tealdeer, as (name, TOML source) pairs.
+const BUILTIN_THEMES: &[(&str, &str)] = &[
+    ("default", include_str!("themes/default.toml")),
+    ("solarized-dark", include_str!("themes/solarized-dark.toml")),
+    ("high-contrast", include_str!("themes/high-contrast.toml")),
+    (
+        "monochrome-bold",
+        include_str!("themes/monochrome-bold.toml"),
+    ),
+];
+
+/// Load the theme with the specified name.
+///
+/// Theme files (`<name>.toml`) in the themes directory below the config
+/// directory take precedence over the built-in themes.
+fn load_theme(name: &str) -> Result<RawStyleConfig, TealdeerError> {
+    if name.contains(&['/', '\\'][..]) {
+        return Err(ConfigError(format!("Invalid theme name: {}", name), None));
+    }
+
+    let (config_dir, _) = get_config_dir()?;
+    let theme_file_path = config_dir
+        .join(THEMES_DIR_NAME)
+        .join(format!("{}.toml", name));
+    let contents = if theme_file_path.is_file() {
+        debug!("Loading theme from {:?}", &theme_file_path);
+        fs::read_to_string(&theme_file_path).map_err(map_io_err_to_config_err)?
+    } else if let Some((_, contents)) = BUILTIN_THEMES.iter().find(|(theme, _)| *theme == name) {
+        (*contents).to_string()
+    } else {
+        return Err(ConfigError(format!("Unknown theme: {}", name), None));
+    };
+
+    toml::from_str(&contents)
+        .map_err(|err| ConfigError(format!("Failed to parse theme {}", name), Some(err.into())))
+}
+
 fn map_io_err_to_config_err(e: IoError) -> TealdeerError {
-    ConfigError(format!("Io Error: {}", e))
+    ConfigError("Io Error".into(), Some(e.into()))
 }
 
 impl Config {
-    pub fn load(enable_styles: bool) -> Result<Self, TealdeerError> {
+    /// Load the config file, if there is one.
+    ///
+    /// The `theme` argument overrides the theme from the config file. Colors
+    /// are mapped to the nearest colors supported by `color_depth`.
+    pub fn load(
+        enable_styles: bool,
+        theme: Option<&str>,
+        color_depth: ColorDepth,
+    ) -> Result<Self, TealdeerError> {
         debug!("Loading config");
 
         // Determine path
         let (config_file_path, _) = get_config_path()
-            .map_err(|e| ConfigError(format!("Could not determine config path: {}", e)))?;
+            .map_err(|e| ConfigError("Could not determine config path".into(), Some(e.into())))?;
 
         // Load raw config
-        let raw_config: RawConfig = if config_file_path.exists() && config_file_path.is_file() {
+        let mut raw_config: RawConfig = if config_file_path.exists() && config_file_path.is_file() {
             let mut config_file =
                 fs::File::open(config_file_path).map_err(map_io_err_to_config_err)?;
             let mut contents = String::new();
             let _ = config_file
                 .read_to_string(&mut contents)
                 .map_err(map_io_err_to_config_err)?;
-            toml::from_str(&contents)
-                .map_err(|err| ConfigError(format!("Failed to parse config file: {}", err)))?
+            toml::from_str(&contents).map_err(|err| {
+                ConfigError("Failed to parse config file".into(), Some(err.into()))
+            })?
         } else {
             RawConfig::new()
         };
+        if raw_config.directories.sources.is_empty() {
+            return Err(ConfigError(
+                "The list of page sources (directories.sources) is empty".into(),
+                None,
+            ));
+        }
+
+        // Layer the configured styles on top of the theme
+        if let Some(theme) = theme {
+            raw_config.style.theme = Some(theme.into());
+        }
+        if let Some(ref theme) = raw_config.style.theme {
+            let theme = load_theme(theme)?;
+            raw_config.style = raw_config.style.layered_over(&theme);
+        }
+        raw_config.style = raw_config.style.downsample(color_depth);
 
         // Convert to config
         let mut config = Self::from(raw_config);
 
         // Potentially override styles
         if !enable_styles {
-            config.style = StyleConfig {
-                command_name: Style::default(),
-                description: Style::default(),
-                example_text: Style::default(),
-                example_code: Style::default(),
-                example_variable: Style::default(),
-            };
+            config.style = StyleConfig::default();
         }
 
         Ok(config)
@@ -314,8 +621,9 @@ pub fn get_config_dir() -> Result<(PathBuf, PathSource), TealdeerError> {
     // Otherwise, fall back to the user config directory.
     match get_app_root(AppDataType::UserConfig, &crate::APP_INFO) {
         Ok(dirs) => Ok((dirs, PathSource::OsConvention)),
-        Err(_) => Err(ConfigError(
+        Err(e) => Err(ConfigError(
             "Could not determine the user config directory.".into(),
+            Some(e.into()),
         )),
     }
 }
@@ -337,30 +645,40 @@ pub fn make_default_config() -> Result<PathBuf, TealdeerError> {
     // Ensure that config directory exists
     if !config_dir.exists() {
         if let Err(e) = fs::create_dir_all(&config_dir) {
-            return Err(ConfigError(format!(
-                "Could not create config directory: {}",
-                e
-            )));
+            return Err(ConfigError(
+                "Could not create config directory".into(),
+                Some(e.into()),
+            ));
         }
     } else if !config_dir.is_dir() {
-        return Err(ConfigError(format!(
-            "Config directory could not be created: {} already exists but is not a directory",
-            config_dir.to_string_lossy(),
-        )));
+        return Err(ConfigError(
+            format!(
+                "Config directory could not be created: {} already exists but is not a directory",
+                config_dir.to_string_lossy(),
+            ),
+            None,
+        ));
     }
 
     // Ensure that a config file doesn't get overwritten
     let config_file_path = config_dir.join(CONFIG_FILE_NAME);
     if config_file_path.is_file() {
-        return Err(ConfigError(format!(
-            "A configuration file already exists at {}, no action was taken.",
-            config_file_path.to_str().unwrap()
-        )));
+        return Err(ConfigError(
+            format!(
+                "A configuration file already exists at {}, no action was taken.",
+                config_file_path.to_str().unwrap()
+            ),
+            None,
+        ));
     }
 
     // Create default config
-    let serialized_config = toml::to_string(&RawConfig::new())
-        .map_err(|err| ConfigError(format!("Failed to serialize default config: {}", err)))?;
+    let serialized_config = toml::to_string(&RawConfig::new()).map_err(|err| {
+        ConfigError(
+            "Failed to serialize default config".into(),
+            Some(err.into()),
+        )
+    })?;
 
     // Write default config
     let mut config_file = fs::File::create(&config_file_path).map_err(map_io_err_to_config_err)?;
@@ -371,6 +689,85 @@ pub fn make_default_config() -> Result<PathBuf, TealdeerError> {
     Ok(config_file_path)
 }
 
+#[test]
+fn test_builtin_themes() {
+    for (name, contents) in BUILTIN_THEMES {
+        let theme: Result<RawStyleConfig, _> = toml::from_str(contents);
+        assert!(theme.is_ok(), "Invalid built-in theme {}", name);
+    }
+}
+
+#[test]
+fn test_theme_layering() {
+    let style_config = RawStyleConfig {
+        example_text: Some(RawStyle {
+            bold: true,
+            ..RawStyle::default()
+        }),
+        ..RawStyleConfig::default()
+    };
+    let theme: RawStyleConfig = toml::from_str(include_str!("themes/default.toml")).unwrap();
+    let layered = style_config.layered_over(&theme);
+    assert_eq!(layered.example_text.unwrap().foreground, None);
+    assert!(layered.example_text.unwrap().bold);
+    assert_eq!(
+        layered.command_name.unwrap().foreground,
+        Some(RawColor::Cyan)
+    );
+}
+
+#[test]
+fn test_downsample_colors() {
+    let rgb = |r, g, b| RawColor::Rgb { r, g, b };
+
+    // True color terminals get the original colors
+    assert_eq!(rgb(1, 2, 3).downsample(ColorDepth::TrueColor), rgb(1, 2, 3));
+
+    // RGB to 256 colors
+    assert_eq!(
+        rgb(255, 0, 0).downsample(ColorDepth::Ansi256),
+        RawColor::Ansi(196)
+    );
+    assert_eq!(
+        rgb(128, 128, 128).downsample(ColorDepth::Ansi256),
+        RawColor::Ansi(244)
+    );
+    assert_eq!(
+        RawColor::Ansi(42).downsample(ColorDepth::Ansi256),
+        RawColor::Ansi(42)
+    );
+
+    // RGB and 256 colors to 16 colors
+    assert_eq!(
+        rgb(250, 20, 10).downsample(ColorDepth::Ansi16),
+        RawColor::Red
+    );
+    assert_eq!(
+        rgb(20, 180, 30).downsample(ColorDepth::Ansi16),
+        RawColor::Green
+    );
+    assert_eq!(
+        RawColor::Ansi(12).downsample(ColorDepth::Ansi16),
+        RawColor::Blue
+    );
+    assert_eq!(
+        RawColor::Ansi(231).downsample(ColorDepth::Ansi16),
+        RawColor::White
+    );
+    assert_eq!(
+        rgb(127, 127, 127).downsample(ColorDepth::Ansi16),
+        RawColor::White
+    );
+    assert_eq!(
+        RawColor::Ansi(244).downsample(ColorDepth::Ansi16),
+        RawColor::White
+    );
+    assert_eq!(
+        RawColor::Cyan.downsample(ColorDepth::Ansi16),
+        RawColor::Cyan
+    );
+}
+
 #[test]
 fn test_serialize_deserialize() {
     let raw_config = RawConfig::new();
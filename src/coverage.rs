@@ -0,0 +1,483 @@
+//! Reports on how many pages are translated into each language.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::iter;
+use std::path::Path;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::TealdeerError::{self, CacheError, WriteError};
+use crate::source::{PageContent, PageSource};
+
+/// The translation coverage of a platform.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlatformCoverage {
+    pub platform: String,
+    /// The number of English pages that are translated
+    pub translated: usize,
+    /// The number of English pages
+    pub total: usize,
+    pub percentage: f64,
+}
+
+/// The translation coverage of a language.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageCoverage {
+    pub language: String,
+    pub platforms: Vec<PlatformCoverage>,
+    pub translated: usize,
+    pub total: usize,
+    pub percentage: f64,
+    /// The English pages without a translation, as `<platform>/<name>`
+    pub missing: Vec<String>,
+    /// The translations whose English page changed since they were last
+    /// updated, as `<platform>/<name>`
+    pub outdated: Vec<String>,
+    /// The translations that could not be checked, as
+    /// `<platform>/<name>: <error>`
+    pub errors: Vec<String>,
+}
+
+/// The content hashes of a translation and of the English page at the time
+/// the translation was last changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationHash {
+    pub translation: String,
+    pub upstream: String,
+}
+
+/// The upstream content hashes of all translations in the cache, by
+/// `<language>/<platform>/<name>`.
+///
+/// The pages don't record which English version they were translated from,
+/// so the hashes are recorded on every cache update: a translation that
+/// changed is assumed to be up to date with the current English page, one
+/// that didn't change keeps the English hash it was recorded with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranslationHashes {
+    pages: BTreeMap<String, TranslationHash>,
+}
+
+impl TranslationHashes {
+    /// Load the hashes from a file. A missing file means that no hashes were
+    /// recorded yet.
+    pub fn load(path: &Path) -> Result<Self, TealdeerError> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                CacheError(
+                    format!(
+                        "Could not parse the translation hashes ({})",
+                        path.display()
+                    ),
+                    Some(e.into()),
+                )
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(CacheError(
+                format!("Could not read the translation hashes ({})", path.display()),
+                Some(e.into()),
+            )),
+        }
+    }
+
+    /// Save the hashes to a file.
+    pub fn save(&self, path: &Path) -> Result<(), TealdeerError> {
+        let json = serde_json::to_string(self).map_err(|e| {
+            CacheError(
+                "Could not serialize the translation hashes".into(),
+                Some(e.into()),
+            )
+        })?;
+        fs::write(path, json).map_err(|e| {
+            CacheError(
+                format!(
+                    "Could not write the translation hashes ({})",
+                    path.display()
+                ),
+                Some(e.into()),
+            )
+        })
+    }
+
+    /// Return the hashes for the translations in the source, keeping the
+    /// recorded upstream hash of every translation that didn't change.
+    ///
+    /// Translations that can't be read are left out, they are reported by
+    /// `coverage_report`.
+    pub fn updated(&self, source: &dyn PageSource) -> Result<Self, TealdeerError> {
+        let mut english_hashes = HashMap::new();
+        let mut pages = BTreeMap::new();
+        let translations = source
+            .entries(None)?
+            .into_iter()
+            .filter_map(|(name, info)| match (info.platform, info.language) {
+                (Some(platform), Some(language)) if language != "en" => {
+                    Some((name, platform, language))
+                }
+                _ => None,
+            });
+        for (name, platform, language) in translations {
+            let translation = match page_hash(source, &name, &platform, &language) {
+                Ok(Some(hash)) => hash,
+                Ok(None) | Err(_) => continue,
+            };
+            let key = format!("{}/{}/{}", language, platform, name);
+            let upstream = match self.pages.get(&key) {
+                Some(recorded) if recorded.translation == translation => recorded.upstream.clone(),
+                _ => match english_hash(&mut english_hashes, source, &name, &platform) {
+                    Ok(Some(hash)) => hash,
+                    Ok(None) | Err(_) => continue,
+                },
+            };
+            pages.insert(
+                key,
+                TranslationHash {
+                    translation,
+                    upstream,
+                },
+            );
+        }
+        Ok(Self { pages })
+    }
+}
+
+fn percentage(translated: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        // Page counts are far too small to lose precision
+        #[allow(clippy::cast_precision_loss)]
+        let percentage = translated as f64 * 100.0 / total as f64;
+        percentage
+    }
+}
+
+/// Return the FNV-1a hash of the page content as a hex string.
+///
+/// Line endings are normalized, so only changes to the text itself count.
+/// The hash is stored in the cache, so it must not change between releases
+/// (unlike the hashers in `std`).
+fn content_hash(content: &PageContent) -> Result<String, TealdeerError> {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for line in content.reader()?.lines() {
+        let line = line.map_err(|e| CacheError("Could not read page".into(), Some(e.into())))?;
+        for byte in line.trim_end().bytes().chain(iter::once(b'\n')) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    Ok(format!("{:016x}", hash))
+}
+
+/// Return the content hash of a page, or `None` if the language doesn't
+/// have the page.
+fn page_hash(
+    source: &dyn PageSource,
+    name: &str,
+    platform: &str,
+    language: &str,
+) -> Result<Option<String>, TealdeerError> {
+    match source.find(name, Some(platform), &[language.to_string()]) {
+        // Don't fall back to the `common` page of another platform
+        Some(page) if page.info.platform.as_deref() == Some(platform) => {
+            content_hash(&page.content).map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Return the content hash of an English page. The English pages are
+/// compared with every language, so the hashes are memoized.
+fn english_hash(
+    hashes: &mut HashMap<(String, String), Option<String>>,
+    source: &dyn PageSource,
+    name: &str,
+    platform: &str,
+) -> Result<Option<String>, TealdeerError> {
+    let key = (platform.to_string(), name.to_string());
+    if let Some(hash) = hashes.get(&key) {
+        return Ok(hash.clone());
+    }
+    let hash = page_hash(source, name, platform, "en")?;
+    hashes.insert(key, hash.clone());
+    Ok(hash)
+}
+
+/// Return the pages of a language by platform.
+fn pages_by_platform(
+    source: &dyn PageSource,
+    language: &str,
+) -> Result<BTreeMap<String, BTreeSet<String>>, TealdeerError> {
+    let mut pages: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (name, info) in source.entries(Some(language))? {
+        if let Some(platform) = info.platform {
+            pages.entry(platform).or_default().insert(name);
+        }
+    }
+    Ok(pages)
+}
+
+/// Compare the translations in the given languages with the English pages.
+///
+/// Only pages that exist in English are taken into account. A translation
+/// is outdated if the English page changed since the upstream hash in
+/// `hashes` was recorded. Pages that can't be read are listed in the
+/// `errors` of their language.
+pub fn coverage_report(
+    source: &dyn PageSource,
+    languages: &[String],
+    hashes: &TranslationHashes,
+) -> Result<Vec<LanguageCoverage>, TealdeerError> {
+    let english = pages_by_platform(source, "en")?;
+    let mut english_hashes = HashMap::new();
+    let mut report = Vec::new();
+    for language in languages.iter().filter(|&language| language != "en") {
+        let translations = pages_by_platform(source, language)?;
+        let mut coverage = LanguageCoverage {
+            language: language.clone(),
+            platforms: Vec::new(),
+            translated: 0,
+            total: 0,
+            percentage: 0.0,
+            missing: Vec::new(),
+            outdated: Vec::new(),
+            errors: Vec::new(),
+        };
+        for (platform, pages) in &english {
+            let mut translated = 0;
+            for name in pages {
+                let page = format!("{}/{}", platform, name);
+                let is_translated = translations
+                    .get(platform)
+                    .is_some_and(|translations| translations.contains(name));
+                if !is_translated {
+                    coverage.missing.push(page);
+                    continue;
+                }
+                translated += 1;
+                let translation = match page_hash(source, name, platform, language) {
+                    Ok(hash) => hash,
+                    Err(e) => {
+                        coverage.errors.push(format!("{}: {}", page, e));
+                        continue;
+                    }
+                };
+                // Without a recorded hash, or if the translation changed since
+                // it was recorded, the recorded upstream hash doesn't apply
+                let recorded = match hashes.pages.get(&format!("{}/{}", language, page)) {
+                    Some(recorded) if translation.as_ref() == Some(&recorded.translation) => {
+                        recorded
+                    }
+                    _ => continue,
+                };
+                match english_hash(&mut english_hashes, source, name, platform) {
+                    Ok(Some(upstream)) if upstream != recorded.upstream => {
+                        coverage.outdated.push(page);
+                    }
+                    Ok(_) => {}
+                    Err(e) => coverage.errors.push(format!("{}: {}", page, e)),
+                }
+            }
+            coverage.platforms.push(PlatformCoverage {
+                platform: platform.clone(),
+                translated,
+                total: pages.len(),
+                percentage: percentage(translated, pages.len()),
+            });
+            coverage.translated += translated;
+            coverage.total += pages.len();
+        }
+        coverage.percentage = percentage(coverage.translated, coverage.total);
+        report.push(coverage);
+    }
+    Ok(report)
+}
+
+/// Write the coverage of every language and platform as a table. With
+/// `details`, the missing, outdated and unreadable pages of each language are listed
+/// after the table.
+pub fn write_coverage_table(
+    writer: &mut dyn Write,
+    report: &[LanguageCoverage],
+    details: bool,
+) -> Result<(), TealdeerError> {
+    let mut rows = vec![[
+        "Language".to_string(),
+        "Platform".into(),
+        "Translated".into(),
+        "Total".into(),
+        "Coverage".into(),
+    ]];
+    for coverage in report {
+        let platforms = coverage.platforms.iter().map(|platform| {
+            (
+                platform.platform.as_str(),
+                platform.translated,
+                platform.total,
+                platform.percentage,
+            )
+        });
+        let all = std::iter::once((
+            "all",
+            coverage.translated,
+            coverage.total,
+            coverage.percentage,
+        ));
+        for (platform, translated, total, percentage) in platforms.chain(all) {
+            rows.push([
+                coverage.language.clone(),
+                platform.into(),
+                translated.to_string(),
+                total.to_string(),
+                format!("{:.1}%", percentage),
+            ]);
+        }
+    }
+
+    let widths: Vec<usize> = (0..5)
+        .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+        .collect();
+    for row in &rows {
+        writeln!(
+            writer,
+            "{:<w0$}  {:<w1$}  {:>w2$}  {:>w3$}  {:>w4$}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4],
+        )
+        .map_err(WriteError)?;
+    }
+
+    if details {
+        for coverage in report {
+            for (label, pages) in &[
+                ("Missing", &coverage.missing),
+                ("Outdated", &coverage.outdated),
+                ("Errors", &coverage.errors),
+            ] {
+                writeln!(writer, "\n{} ({}):", label, coverage.language).map_err(WriteError)?;
+                for page in *pages {
+                    writeln!(writer, "  {}", page).map_err(WriteError)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{coverage_report, write_coverage_table, TranslationHashes};
+    use crate::source::{CacheSource, MemorySource};
+
+    fn source(tar: &str, ls: &str, ls_de: &str) -> MemorySource {
+        MemorySource::new()
+            .with_page("en", "common", "tar", tar)
+            .with_page("en", "common", "ls", ls)
+            .with_page("en", "linux", "apt", "# apt\n\n- Update:\n\n`apt update`\n")
+            .with_page(
+                "en",
+                "linux",
+                "dnf",
+                "# dnf\n\n- Update:\n\n`dnf upgrade`\n",
+            )
+            .with_page(
+                "de",
+                "common",
+                "tar",
+                "# tar\n\n- Erstellen:\n\n`tar cf {{datei}}`\n",
+            )
+            .with_page("de", "common", "ls", ls_de)
+            .with_page(
+                "de",
+                "linux",
+                "apt",
+                "# apt\n\n- Aktualisieren:\n\n`apt update`\n",
+            )
+            .with_page("de", "linux", "orphan", "# orphan\n")
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        let old = source(
+            "# tar\n\n- Create:\n\n`tar cf {{file}}`\n",
+            "# ls\n\n- List:\n\n`ls`\n",
+            "# ls\n\n- Auflisten:\n\n`ls`\n",
+        );
+        let hashes = TranslationHashes::default().updated(&old).unwrap();
+
+        // The English tar page changed, ls changed in both languages
+        let new = source(
+            "# tar\n\n- Create:\n\n`tar cf {{file}}`\n\n- Extract:\n\n`tar xf {{file}}`\n",
+            "# ls\n\n- List:\n\n`ls -l`\n",
+            "# ls\n\n- Auflisten:\n\n`ls -l`\n",
+        );
+        let hashes = hashes.updated(&new).unwrap();
+        let report = coverage_report(&new, &["en".into(), "de".into()], &hashes).unwrap();
+        assert_eq!(report.len(), 1);
+        let coverage = &report[0];
+        assert_eq!(coverage.language, "de");
+        assert_eq!((coverage.translated, coverage.total), (3, 4));
+        assert!((coverage.percentage - 75.0).abs() < f64::EPSILON);
+        assert_eq!(coverage.missing, vec!["linux/dnf"]);
+        assert_eq!(coverage.outdated, vec!["common/tar"]);
+        assert!(coverage.errors.is_empty());
+
+        let mut output = Vec::new();
+        write_coverage_table(&mut output, &report, true).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Language  Platform  Translated  Total  Coverage\n\
+             de        common             2      2    100.0%\n\
+             de        linux              1      2     50.0%\n\
+             de        all                3      4     75.0%\n\
+             \n\
+             Missing (de):\n  linux/dnf\n\
+             \n\
+             Outdated (de):\n  common/tar\n\
+             \n\
+             Errors (de):\n"
+        );
+    }
+
+    #[test]
+    fn test_coverage_report_without_hashes() {
+        let source = source("# tar\n", "# ls\n", "# ls\n");
+        let report =
+            coverage_report(&source, &["de".into()], &TranslationHashes::default()).unwrap();
+        assert!(report[0].outdated.is_empty());
+    }
+
+    #[test]
+    fn test_coverage_report_unreadable_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let page = |lang_dir: &str, name: &str, content: &[u8]| {
+            let platform_dir = dir.path().join(lang_dir).join("common");
+            std::fs::create_dir_all(&platform_dir).unwrap();
+            std::fs::write(platform_dir.join(name), content).unwrap();
+        };
+        page("pages", "ls.md", b"# ls\n");
+        page("pages", "tar.md", b"# tar\n");
+        page("pages.de", "ls.md", b"# ls\n\n> Auflisten \xff\n");
+        page("pages.de", "tar.md", b"# tar\n");
+
+        let source = CacheSource::new(dir.path());
+        let hashes = TranslationHashes::default().updated(&source).unwrap();
+        page("pages", "tar.md", b"# tar\n\n> Archive.\n");
+        let report = coverage_report(&source, &["de".into()], &hashes).unwrap();
+        let coverage = &report[0];
+        assert_eq!((coverage.translated, coverage.total), (2, 2));
+        assert_eq!(coverage.outdated, vec!["common/tar"]);
+        assert_eq!(coverage.errors.len(), 1);
+        assert!(coverage.errors[0].starts_with("common/ls: Could not read page"));
+    }
+}
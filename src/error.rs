@@ -1,39 +1,139 @@
 use reqwest::Error as ReqwestError;
+use std::error::Error;
 use std::fmt;
+use std::io;
+
+/// The underlying error of a `TealdeerError`.
+pub type Source = Box<dyn Error + Send + Sync + 'static>;
+
+/// Exit code for errors without a more specific exit code.
+pub const EXIT_FAILURE: i32 = 1;
+/// Exit code if the requested page was not found.
+pub const EXIT_PAGE_NOT_FOUND: i32 = 2;
+/// Exit code if the cache has not been downloaded yet.
+pub const EXIT_CACHE_MISSING: i32 = 3;
+/// Exit code if updating the cache failed.
+pub const EXIT_UPDATE_FAILED: i32 = 4;
+/// Exit code if the config file or a theme is invalid.
+pub const EXIT_CONFIG_INVALID: i32 = 5;
 
 #[derive(Debug)]
 #[allow(clippy::pub_enum_variant_names)]
 pub enum TealdeerError {
-    CacheError(String),
-    ConfigError(String),
-    UpdateError(String),
-    WriteError(String),
+    /// The requested page was not found in any page source
+    PageNotFound(String),
+    /// The cache has not been downloaded yet
+    CacheMissing,
+    /// The cache could not be read or modified
+    CacheError(String, Option<Source>),
+    /// The config file or a theme could not be loaded
+    ConfigError(String, Option<Source>),
+    /// The pages could not be downloaded or extracted
+    UpdateError(String, Option<Source>),
+    /// The output could not be written
+    WriteError(io::Error),
+    /// A page could not be parsed
+    ParseError(String, Option<Source>),
 }
 
 impl TealdeerError {
-    pub fn message(&self) -> &str {
+    /// Return the error message, without the underlying error.
+    pub fn message(&self) -> String {
+        match self {
+            Self::PageNotFound(name) => format!("Page {} not found in cache", name),
+            Self::CacheMissing => "Cache not found. Please run `tldr --update`.".into(),
+            Self::CacheError(msg, _)
+            | Self::ConfigError(msg, _)
+            | Self::UpdateError(msg, _)
+            | Self::ParseError(msg, _) => msg.clone(),
+            Self::WriteError(_) => "Could not write to stdout".into(),
+        }
+    }
+
+    /// Return an identifier of the kind of error, e.g. for machine-readable output.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::PageNotFound(_) => "page_not_found",
+            Self::CacheMissing => "cache_missing",
+            Self::CacheError(..) => "cache_error",
+            Self::ConfigError(..) => "config_error",
+            Self::UpdateError(..) => "update_error",
+            Self::WriteError(_) => "write_error",
+            Self::ParseError(..) => "parse_error",
+        }
+    }
+
+    /// Return the exit code of the `tldr` binary for this error.
+    pub fn exit_code(&self) -> i32 {
         match self {
-            Self::CacheError(msg)
-            | Self::ConfigError(msg)
-            | Self::UpdateError(msg)
-            | Self::WriteError(msg) => msg,
+            Self::PageNotFound(_) => EXIT_PAGE_NOT_FOUND,
+            Self::CacheMissing => EXIT_CACHE_MISSING,
+            Self::UpdateError(..) => EXIT_UPDATE_FAILED,
+            Self::ConfigError(..) => EXIT_CONFIG_INVALID,
+            Self::CacheError(..) | Self::WriteError(_) | Self::ParseError(..) => EXIT_FAILURE,
         }
     }
 }
 
 impl From<ReqwestError> for TealdeerError {
     fn from(err: ReqwestError) -> Self {
-        Self::UpdateError(format!("HTTP error: {}", err.to_string()))
+        Self::UpdateError("HTTP error".into(), Some(err.into()))
     }
 }
 
 impl fmt::Display for TealdeerError {
+    /// Print the message, followed by the underlying error (if any).
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.source() {
+            Some(source) => write!(f, "{}: {}", self.message(), source),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl Error for TealdeerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            Self::CacheError(e) => write!(f, "CacheError: {}", e),
-            Self::ConfigError(e) => write!(f, "ConfigError: {}", e),
-            Self::UpdateError(e) => write!(f, "UpdateError: {}", e),
-            Self::WriteError(e) => write!(f, "WriteError: {}", e),
+            Self::CacheError(_, source)
+            | Self::ConfigError(_, source)
+            | Self::UpdateError(_, source)
+            | Self::ParseError(_, source) => source
+                .as_ref()
+                .map(|source| &**source as &(dyn Error + 'static)),
+            Self::WriteError(source) => Some(source),
+            Self::PageNotFound(_) | Self::CacheMissing => None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::TealdeerError::{self, ConfigError, PageNotFound};
+    use std::collections::HashSet;
+    use std::error::Error;
+    use std::io;
+
+    #[test]
+    fn test_display_with_source() {
+        let err = ConfigError(
+            "Failed to parse config file".into(),
+            Some(io::Error::new(io::ErrorKind::Other, "invalid key").into()),
+        );
+        assert_eq!(err.to_string(), "Failed to parse config file: invalid key");
+        assert_eq!(err.source().unwrap().to_string(), "invalid key");
+        assert_eq!(err.exit_code(), super::EXIT_CONFIG_INVALID);
+    }
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors: Vec<TealdeerError> = vec![
+            PageNotFound("tar".into()),
+            TealdeerError::CacheMissing,
+            TealdeerError::UpdateError("HTTP error".into(), None),
+            ConfigError("Unknown theme: foo".into(), None),
+            TealdeerError::CacheError("Could not open file".into(), None),
+        ];
+        let codes: HashSet<i32> = errors.iter().map(TealdeerError::exit_code).collect();
+        assert_eq!(codes.len(), errors.len());
+    }
+}
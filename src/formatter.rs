@@ -1,108 +1,355 @@
-//! Functions related to formatting and printing lines from a `Tokenizer`.
+//! Functions related to formatting and printing pages to an ANSI terminal.
 
-use std::io::{BufRead, Write};
+use std::io::Write;
 
-use ansi_term::{ANSIString, ANSIStrings};
+use ansi_term::{ANSIString, ANSIStrings, Style};
 use log::debug;
 
-use crate::config::Config;
+use crate::config::{Config, StyleConfig};
 use crate::error::TealdeerError::{self, WriteError};
-use crate::tokenizer::Tokenizer;
-use crate::types::LineType;
+use crate::inline::{parse_inline, InlineElement};
+use crate::render::{PageOutline, Renderer};
+use crate::shell::{lex, ShellToken};
+use crate::types::{LineType, PageInfo};
 
-fn highlight_command<'a>(
-    command: &'a str,
-    example_code: &'a str,
-    config: &Config,
-    parts: &mut Vec<ANSIString<'a>>,
-) {
-    let mut code_part_end_pos = 0;
-    while let Some(command_start) = example_code[code_part_end_pos..].find(&command) {
-        let code_part = &example_code[code_part_end_pos..code_part_end_pos + command_start];
-        parts.push(config.style.example_code.paint(code_part));
-        if code_part_end_pos == 0 {
-            // Only highlight command names at the start of the line ...
-            parts.push(config.style.command_name.paint(command));
-        } else {
-            let char_before_command = example_code
-                .chars()
-                .nth(code_part_end_pos + command_start - 1);
-            if char_before_command.filter(|c| c.is_whitespace()).is_some() {
-                // ... or when preceded by a whitespace character.
-                parts.push(config.style.command_name.paint(command));
-            } else {
-                parts.push(config.style.example_code.paint(command));
+/// Layer an inline element style on top of the style of the surrounding text.
+///
+/// Colors of the inline style take precedence, attributes are combined.
+fn layer_style(inline: Style, base: Style) -> Style {
+    Style {
+        foreground: inline.foreground.or(base.foreground),
+        background: inline.background.or(base.background),
+        is_bold: inline.is_bold || base.is_bold,
+        is_dimmed: inline.is_dimmed || base.is_dimmed,
+        is_italic: inline.is_italic || base.is_italic,
+        is_underline: inline.is_underline || base.is_underline,
+        is_blink: inline.is_blink || base.is_blink,
+        is_reverse: inline.is_reverse || base.is_reverse,
+        is_hidden: inline.is_hidden || base.is_hidden,
+        is_strikethrough: inline.is_strikethrough || base.is_strikethrough,
+    }
+}
+
+/// Format descriptions and example text, including inline `code`, <links>
+/// and *emphasis*. The markdown delimiters are stripped.
+fn format_inline(text: &str, base: Style, config: &Config) -> String {
+    let parts: Vec<ANSIString> = parse_inline(text)
+        .into_iter()
+        .map(|element| match element {
+            InlineElement::Text(text) => base.paint(text),
+            InlineElement::Code(code) => layer_style(config.style.inline_code, base).paint(code),
+            InlineElement::Link(link) => layer_style(config.style.inline_link, base).paint(link),
+            InlineElement::Emphasis(text) => {
+                layer_style(config.style.inline_emphasis, base).paint(text)
             }
-        }
+        })
+        .collect();
 
-        code_part_end_pos += command_start + command.len();
-    }
-    parts.push(
-        config
-            .style
-            .example_code
-            .paint(&example_code[code_part_end_pos..]),
-    );
+    ANSIStrings(&parts).to_string()
 }
 
 /// Format and highlight code examples including variables in {{ curly braces }}.
-fn format_code(command: &str, text: &str, config: &Config) -> String {
-    let mut parts = Vec::new();
-    for between_variables in text.split("}}") {
-        if let Some(variable_start) = between_variables.find("{{") {
-            let example_code = &between_variables[..variable_start];
-            let example_variable = &between_variables[variable_start + 2..];
-
-            highlight_command(&command, &example_code, &config, &mut parts);
-            parts.push(config.style.example_variable.paint(example_variable));
+///
+/// Flags, strings, operators, environment variables and subcommands are
+/// layered on top of the example code style, and `highlight` is layered on
+/// top of everything.
+fn format_code(command: &str, text: &str, config: &Config, highlight: Style) -> String {
+    let style = &config.style;
+    let parts: Vec<ANSIString> = lex(text, command)
+        .into_iter()
+        .map(|token| {
+            let (token_style, text) = match token {
+                ShellToken::Text(text) => (style.example_code, text),
+                ShellToken::CommandName(text) => (style.command_name, text),
+                ShellToken::Variable(text) => (style.example_variable, text),
+                ShellToken::Subcommand(text) => (
+                    layer_style(style.example_subcommand, style.example_code),
+                    text,
+                ),
+                ShellToken::Flag(text) => {
+                    (layer_style(style.example_flag, style.example_code), text)
+                }
+                ShellToken::String(text) => {
+                    (layer_style(style.example_string, style.example_code), text)
+                }
+                ShellToken::Operator(text) => (
+                    layer_style(style.example_operator, style.example_code),
+                    text,
+                ),
+                ShellToken::EnvVar(text) => {
+                    (layer_style(style.example_env_var, style.example_code), text)
+                }
+            };
+            layer_style(highlight, token_style).paint(text)
+        })
+        .collect();
+
+    ANSIStrings(&parts).to_string()
+}
+
+/// Return whether the text contains any of the words, ignoring case.
+fn mentions(text: &str, words: &[String]) -> bool {
+    text.split(|c: char| !c.is_alphanumeric())
+        .any(|part| words.iter().any(|word| part.eq_ignore_ascii_case(word)))
+}
+
+/// Print the page header, consisting of the title and (if known) the platform
+/// and language of the page.
+fn print_header(
+    writer: &mut dyn Write,
+    title: &str,
+    page_info: &PageInfo,
+    config: &Config,
+) -> Result<(), TealdeerError> {
+    let details: Vec<&str> = page_info
+        .platform
+        .iter()
+        .chain(page_info.language.iter())
+        .map(String::as_str)
+        .collect();
+    let header = if details.is_empty() {
+        title.to_string()
+    } else {
+        format!("{} ({})", title, details.join(", "))
+    };
+    if !config.display.compact {
+        writeln!(writer).map_err(WriteError)?;
+    }
+    writeln!(writer, "  {}", config.style.title.paint(header)).map_err(WriteError)
+}
+
+/// Print the separator between a page and a custom patch.
+fn print_separator(
+    writer: &mut dyn Write,
+    patch_name: &str,
+    config: &Config,
+) -> Result<(), TealdeerError> {
+    writeln!(
+        writer,
+        "  {}",
+        config.style.title.paint(format!("--- {} ---", patch_name))
+    )
+    .map_err(WriteError)
+}
+
+/// Renders pages for an ANSI terminal.
+///
+/// If the header is enabled in the display config, the page title is
+/// printed as a header.
+#[derive(Debug, Clone)]
+pub struct TerminalRenderer {
+    config: Config,
+    /// The page info for the header, unset while rendering a patch
+    page_info: Option<PageInfo>,
+    /// The command name, used to highlight it in example code
+    command: String,
+    /// The words that examples are highlighted for
+    highlight_words: Vec<String>,
+}
+
+impl TerminalRenderer {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            page_info: None,
+            command: String::new(),
+            highlight_words: Vec::new(),
+        }
+    }
+
+    /// Highlight the example text and code lines that mention any of the
+    /// words with the `highlight` style.
+    pub fn with_highlight(mut self, words: &[String]) -> Self {
+        self.highlight_words = words.to_vec();
+        self
+    }
+
+    /// Return the highlight style for a line of an example.
+    fn highlight(&self, text: &str) -> Style {
+        if mentions(text, &self.highlight_words) {
+            self.config.style.highlight
         } else {
-            highlight_command(&command, &between_variables, &config, &mut parts);
+            Style::default()
         }
     }
 
-    ANSIStrings(&parts).to_string()
+    /// Create a renderer for plain text without any styles.
+    pub fn plain(config: &Config) -> Self {
+        Self::new(&Config {
+            style: StyleConfig::default(),
+            ..config.clone()
+        })
+    }
 }
 
-/// Print a token stream to an ANSI terminal.
-pub fn print_lines<T, R>(
-    writer: &mut T,
-    tokenizer: &mut Tokenizer<R>,
-    config: &Config,
-) -> Result<(), TealdeerError>
-where
-    T: Write,
-    R: BufRead,
-{
-    let mut command = String::new();
-    while let Some(token) = tokenizer.next_token() {
+impl Renderer for TerminalRenderer {
+    fn start(
+        &mut self,
+        writer: &mut dyn Write,
+        page_info: &PageInfo,
+        patch_name: Option<&str>,
+    ) -> Result<(), TealdeerError> {
+        self.command.clear();
+        match patch_name {
+            Some(patch_name) => {
+                self.page_info = None;
+                if self.config.display.show_patch_separator {
+                    print_separator(writer, patch_name, &self.config)?;
+                }
+            }
+            None => self.page_info = Some(page_info.clone()),
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, writer: &mut dyn Write, token: LineType) -> Result<(), TealdeerError> {
+        let config = &self.config;
         match token {
             LineType::Empty => {
                 if !config.display.compact {
-                    writeln!(writer).map_err(|e| WriteError(e.to_string()))?;
+                    writeln!(writer).map_err(WriteError)?;
                 }
             }
             LineType::Title(title) => {
-                debug!("Ignoring title");
+                match self.page_info {
+                    Some(ref page_info) if config.display.show_title => {
+                        print_header(writer, &title, page_info, config)?;
+                    }
+                    _ => debug!("Ignoring title"),
+                }
 
                 // This is safe as long as the parsed title is only the command,
                 // and tokenizer yields values in order of appearance.
-                command = title;
-                debug!("Detected command name: {}", &command);
+                self.command = title;
+                debug!("Detected command name: {}", &self.command);
             }
             LineType::Description(text) => {
-                writeln!(writer, "  {}", config.style.description.paint(text))
-                    .map_err(|e| WriteError(e.to_string()))?;
+                writeln!(
+                    writer,
+                    "  {}",
+                    format_inline(&text, config.style.description, config)
+                )
+                .map_err(WriteError)?;
             }
             LineType::ExampleText(text) => {
-                writeln!(writer, "  {}", config.style.example_text.paint(text))
-                    .map_err(|e| WriteError(e.to_string()))?;
+                writeln!(
+                    writer,
+                    "  {}",
+                    format_inline(
+                        &text,
+                        layer_style(self.highlight(&text), config.style.example_text),
+                        config
+                    )
+                )
+                .map_err(WriteError)?;
             }
             LineType::ExampleCode(text) => {
-                writeln!(writer, "      {}", &format_code(&command, &text, &config))
-                    .map_err(|e| WriteError(e.to_string()))?;
+                writeln!(
+                    writer,
+                    "      {}",
+                    &format_code(&self.command, &text, config, self.highlight(&text))
+                )
+                .map_err(WriteError)?;
             }
             LineType::Other(text) => debug!("Unknown line type: {:?}", text),
         }
+        Ok(())
+    }
+
+    fn end(&mut self, writer: &mut dyn Write) -> Result<(), TealdeerError> {
+        writeln!(writer).map_err(WriteError)
+    }
+}
+
+/// Print several translations of a page side by side.
+///
+/// The description lines and the examples are paired by position, each line
+/// labeled with the language of its translation. Example code that is the
+/// same in all translations is only printed once.
+pub fn print_paired(
+    writer: &mut dyn Write,
+    outlines: &[PageOutline],
+    config: &Config,
+) -> Result<(), TealdeerError> {
+    let first = match outlines.first() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    let label = |outline: &PageOutline| outline.language.clone().unwrap_or_default();
+    let width = outlines
+        .iter()
+        .map(|outline| label(outline).chars().count())
+        .max()
+        .unwrap_or(0);
+    let labeled = |outline: &PageOutline| {
+        config
+            .style
+            .title
+            .paint(format!("{:<width$}", label(outline), width = width))
+    };
+    let empty_line = |writer: &mut dyn Write| {
+        if config.display.compact {
+            Ok(())
+        } else {
+            writeln!(writer).map_err(WriteError)
+        }
+    };
+
+    if config.display.show_title {
+        let languages: Vec<String> = outlines.iter().map(label).collect();
+        let page_info = PageInfo {
+            platform: first.platform.clone(),
+            language: Some(languages.join(", ")),
+            custom: false,
+        };
+        print_header(writer, &first.name, &page_info, config)?;
+    }
+
+    empty_line(writer)?;
+    for outline in outlines {
+        for line in outline.description.lines() {
+            writeln!(
+                writer,
+                "  {}  {}",
+                labeled(outline),
+                format_inline(line, config.style.description, config)
+            )
+            .map_err(WriteError)?;
+        }
+    }
+
+    let count = outlines
+        .iter()
+        .map(|outline| outline.examples.len())
+        .max()
+        .unwrap_or(0);
+    for i in 0..count {
+        let examples: Vec<_> = outlines
+            .iter()
+            .filter_map(|outline| outline.examples.get(i).map(|example| (outline, example)))
+            .collect();
+        empty_line(writer)?;
+        for (outline, example) in &examples {
+            writeln!(
+                writer,
+                "  {}  {}",
+                labeled(outline),
+                format_inline(&example.description, config.style.example_text, config)
+            )
+            .map_err(WriteError)?;
+        }
+        empty_line(writer)?;
+        let same_code = examples
+            .windows(2)
+            .all(|pair| pair[0].1.command == pair[1].1.command);
+        for (j, (outline, example)) in examples.iter().enumerate() {
+            let code = format_code(&outline.name, &example.command, config, Style::default());
+            if !same_code {
+                writeln!(writer, "  {}      {}", labeled(outline), code).map_err(WriteError)?;
+            } else if j == 0 {
+                writeln!(writer, "      {}", code).map_err(WriteError)?;
+            }
+        }
     }
-    writeln!(writer).map_err(|e| WriteError(e.to_string()))
+    writeln!(writer).map_err(WriteError)
 }
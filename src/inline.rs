@@ -0,0 +1,174 @@
+//! Code to split descriptions and example text into inline markdown elements.
+
+/// An inline element within a description or an example text line.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum InlineElement<'a> {
+    /// Plain text
+    Text(&'a str),
+    /// Inline code wrapped in `backticks`
+    Code(&'a str),
+    /// A link wrapped in `<angle brackets>`
+    Link(&'a str),
+    /// Text wrapped in *single* or **double** asterisks or underscores
+    Emphasis(&'a str),
+}
+
+/// Return whether `text` looks like the target of an autolink.
+fn is_link_target(text: &str) -> bool {
+    !text.is_empty()
+        && !text.contains(char::is_whitespace)
+        && (text.contains("://") || text.starts_with("mailto:"))
+}
+
+/// Return whether an emphasis delimiter can open or close at the given
+/// position, i.e. whether it is not part of a word like `file_name`.
+fn is_word_boundary(c: Option<char>) -> bool {
+    c.is_none_or(|c| !c.is_alphanumeric())
+}
+
+/// Try to parse the element starting at byte offset `start`, return the
+/// element and the byte offset right after it.
+fn parse_element(text: &str, start: usize) -> Option<(InlineElement<'_>, usize)> {
+    let rest = &text[start..];
+    match rest.chars().next()? {
+        '`' => {
+            let end = rest[1..].find('`')? + 1;
+            if end == 1 {
+                return None;
+            }
+            Some((InlineElement::Code(&rest[1..end]), start + end + 1))
+        }
+        '<' => {
+            let end = rest.find('>')?;
+            let target = &rest[1..end];
+            if !is_link_target(target) {
+                return None;
+            }
+            Some((InlineElement::Link(target), start + end + 1))
+        }
+        delimiter if delimiter == '*' || delimiter == '_' => {
+            let len = if rest[1..].starts_with(delimiter) {
+                2
+            } else {
+                1
+            };
+            let delimiter = &rest[..len];
+            if !is_word_boundary(text[..start].chars().next_back()) {
+                return None;
+            }
+            let inner_start = start + len;
+            if text[inner_start..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace)
+            {
+                return None;
+            }
+            let mut search_from = inner_start;
+            while let Some(offset) = text[search_from..].find(delimiter) {
+                let end = search_from + offset;
+                let before = text[..end].chars().next_back();
+                let after = text[end + len..].chars().next();
+                if end > inner_start
+                    && before.is_some_and(|c| !c.is_whitespace())
+                    && is_word_boundary(after)
+                    && !after.is_some_and(|c| delimiter.starts_with(c))
+                {
+                    return Some((InlineElement::Emphasis(&text[inner_start..end]), end + len));
+                }
+                search_from = end + len;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Split a line of text into inline elements.
+///
+/// Delimiters that are not closed are kept as plain text.
+pub fn parse_inline(text: &str) -> Vec<InlineElement<'_>> {
+    let mut elements = Vec::new();
+    let mut text_start = 0;
+    let mut pos = 0;
+    while pos < text.len() {
+        if let Some((element, end)) = parse_element(text, pos) {
+            if text_start < pos {
+                elements.push(InlineElement::Text(&text[text_start..pos]));
+            }
+            elements.push(element);
+            text_start = end;
+            pos = end;
+        } else {
+            pos += text[pos..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    if text_start < text.len() {
+        elements.push(InlineElement::Text(&text[text_start..]));
+    }
+    elements
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_inline, InlineElement::*};
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(parse_inline("Just text."), vec![Text("Just text.")]);
+        assert_eq!(parse_inline(""), vec![]);
+    }
+
+    #[test]
+    fn test_inline_code() {
+        assert_eq!(
+            parse_inline("Use `tar` instead:"),
+            vec![Text("Use "), Code("tar"), Text(" instead:")]
+        );
+        assert_eq!(parse_inline("A lone ` here"), vec![Text("A lone ` here")]);
+        assert_eq!(parse_inline("Empty `` code"), vec![Text("Empty `` code")]);
+    }
+
+    #[test]
+    fn test_link() {
+        assert_eq!(
+            parse_inline("More information: <https://example.com>."),
+            vec![
+                Text("More information: "),
+                Link("https://example.com"),
+                Text(".")
+            ]
+        );
+        assert_eq!(
+            parse_inline("Redirect <input> to a file"),
+            vec![Text("Redirect <input> to a file")]
+        );
+    }
+
+    #[test]
+    fn test_emphasis() {
+        assert_eq!(
+            parse_inline("Do *not* use **this** or _that_"),
+            vec![
+                Text("Do "),
+                Emphasis("not"),
+                Text(" use "),
+                Emphasis("this"),
+                Text(" or "),
+                Emphasis("that")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_emphasis_inside_words() {
+        assert_eq!(
+            parse_inline("Set file_name and other_name"),
+            vec![Text("Set file_name and other_name")]
+        );
+        assert_eq!(
+            parse_inline("Match * or 2 * 3"),
+            vec![Text("Match * or 2 * 3")]
+        );
+    }
+}
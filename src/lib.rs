@@ -0,0 +1,98 @@
+//! A library to fetch, look up, parse and render
+//! [tldr](https://github.com/tldr-pages/tldr) pages.
+//!
+//! This is the library behind the `tldr` binary. A minimal example that
+//! renders a page from the cache to stdout:
+//!
+//! ```no_run
+//! use tealdeer::{render_page, Cache, ColorDepth, Config, OsType, TerminalRenderer, ARCHIVE_URL};
+//!
+//! let config = Config::load(true, None, ColorDepth::TrueColor).unwrap();
+//! let cache = Cache::new(ARCHIVE_URL, OsType::Linux);
+//! let languages = vec!["en".to_string()];
+//! let page = cache
+//!     .find_page("tar", &languages, None)
+//!     .expect("Page not found");
+//! let mut renderer = TerminalRenderer::new(&config);
+//! render_page(&mut renderer, &mut std::io::stdout(), &page).unwrap();
+//! ```
+//
+// Copyright (c) 2015-2020 tealdeer developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be
+// copied, modified, or distributed except according to those terms.
+
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::similar_names)]
+#![allow(clippy::module_name_repetitions)]
+#![allow(clippy::too_many_lines)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::must_use_candidate)]
+#![allow(clippy::missing_panics_doc)]
+
+use app_dirs::AppInfo;
+
+mod alias;
+mod cache;
+mod canonical;
+mod config;
+mod coverage;
+mod error;
+mod formatter;
+mod inline;
+mod linter;
+mod listing;
+mod render;
+mod shell;
+mod source;
+mod tokenizer;
+mod types;
+
+pub use crate::cache::{Cache, PageLookupResult};
+pub use crate::config::Config;
+pub use crate::error::TealdeerError;
+pub use crate::formatter::TerminalRenderer;
+pub use crate::render::{
+    page_outline, render_page, CommandsRenderer, JsonRenderer, OutlineExample, OutlineRenderer,
+    PageOutline, RawRenderer, Renderer,
+};
+pub use crate::source::{
+    ArchiveSource, CacheSource, CustomPagesSource, MemorySource, PageContent, PageSource,
+    SourceChain, SourcePage,
+};
+pub use crate::tokenizer::{TldrFormat, Tokenizer};
+pub use crate::types::{ColorDepth, LineType, OsType, PageInfo};
+
+/// The parts of the library that only the `tldr` binary uses. They are not
+/// part of the public API and may change in any release.
+#[doc(hidden)]
+pub mod internal {
+    pub use crate::alias::{resolve_aliases, AliasTarget};
+    pub use crate::canonical::{canonicalize, convert_v2};
+    pub use crate::config::{
+        get_config_dir, get_config_path, make_default_config, AliasMode, StyleConfig, MAX_CACHE_AGE,
+    };
+    pub use crate::coverage::{coverage_report, write_coverage_table};
+    pub use crate::formatter::print_paired;
+    pub use crate::linter::{lint_page, LintWarning};
+    pub use crate::listing::{
+        list_pages, summarize_pages, write_table, PageFilter, PlatformFilter,
+    };
+    pub use crate::source::suggest_pages;
+    pub use crate::types::{ColorOptions, OutputFormat, PathSource};
+}
+
+/// The application name, used for the cache and config directories.
+pub const NAME: &str = "tealdeer";
+
+pub(crate) const APP_INFO: AppInfo = AppInfo {
+    name: NAME,
+    author: NAME,
+};
+
+/// The URL of the tldr pages archive that the cache is updated from.
+pub const ARCHIVE_URL: &str = "https://github.com/tldr-pages/tldr/archive/master.tar.gz";
@@ -0,0 +1,330 @@
+//! Checks for violations of the tldr style guide
+//! (<https://github.com/tldr-pages/tldr/blob/master/CONTRIBUTING.md>).
+
+use std::fmt;
+
+/// The maximum number of examples a page should have.
+const MAX_EXAMPLES: usize = 8;
+
+/// A style guide rule.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Rule {
+    /// The page does not start with a `# title` line
+    MissingTitle,
+    /// There are no `> description` lines after the title
+    MissingDescription,
+    /// The description doesn't contain a "More information" link
+    MissingMoreInformation,
+    /// An example text does not end with a colon
+    ExampleTextColon,
+    /// An example code line is not wrapped in backticks
+    CodeBackticks,
+    /// A `{{placeholder}}` is empty, nested or not closed
+    MalformedPlaceholder,
+    /// A line ends with whitespace
+    TrailingWhitespace,
+    /// The page has no examples or more than eight
+    ExampleCount,
+}
+
+impl Rule {
+    /// Return the identifier of the rule.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::MissingTitle => "missing-title",
+            Self::MissingDescription => "missing-description",
+            Self::MissingMoreInformation => "missing-more-information",
+            Self::ExampleTextColon => "example-text-colon",
+            Self::CodeBackticks => "code-backticks",
+            Self::MalformedPlaceholder => "malformed-placeholder",
+            Self::TrailingWhitespace => "trailing-whitespace",
+            Self::ExampleCount => "example-count",
+        }
+    }
+}
+
+/// A style guide violation, with 1-based line and column numbers.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct LintWarning {
+    pub line: usize,
+    pub column: usize,
+    pub rule: Rule,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} ({})",
+            self.line,
+            self.column,
+            self.message,
+            self.rule.code()
+        )
+    }
+}
+
+/// Collects the warnings for a page.
+struct Linter {
+    warnings: Vec<LintWarning>,
+}
+
+impl Linter {
+    fn warn<S: Into<String>>(&mut self, line: usize, column: usize, rule: Rule, message: S) {
+        self.warnings.push(LintWarning {
+            line,
+            column,
+            rule,
+            message: message.into(),
+        });
+    }
+
+    /// Check the placeholders in an example code line.
+    fn check_placeholders(&mut self, line_number: usize, code: &str, offset: usize) {
+        let mut open: Option<usize> = None;
+        let mut pos = 0;
+        while pos < code.len() {
+            let rest = &code[pos..];
+            let column = offset + code[..pos].chars().count() + 1;
+            if rest.starts_with("{{") {
+                if open.is_some() {
+                    self.warn(
+                        line_number,
+                        column,
+                        Rule::MalformedPlaceholder,
+                        "Nested placeholder",
+                    );
+                }
+                open = Some(column);
+                pos += 2;
+            } else if rest.starts_with("}}") {
+                match open {
+                    Some(_) if code[..pos].ends_with("{{") => {
+                        self.warn(
+                            line_number,
+                            column - 2,
+                            Rule::MalformedPlaceholder,
+                            "Empty placeholder",
+                        );
+                    }
+                    Some(_) => {}
+                    None => {
+                        self.warn(
+                            line_number,
+                            column,
+                            Rule::MalformedPlaceholder,
+                            "Placeholder closed without being opened",
+                        );
+                    }
+                }
+                open = None;
+                pos += 2;
+            } else {
+                pos += rest.chars().next().map_or(1, char::len_utf8);
+            }
+        }
+        if let Some(column) = open {
+            self.warn(
+                line_number,
+                column,
+                Rule::MalformedPlaceholder,
+                "Placeholder is not closed",
+            );
+        }
+    }
+}
+
+/// Check a page in the markdown format for style guide violations.
+///
+/// The warnings are ordered by line number.
+pub fn lint_page(content: &str) -> Vec<LintWarning> {
+    let mut linter = Linter {
+        warnings: Vec::new(),
+    };
+    let mut title_seen = false;
+    let mut description_seen = false;
+    let mut more_information_seen = false;
+    let mut examples = 0;
+    let mut last_line = 0;
+
+    for (i, line) in content.lines().enumerate() {
+        let line_number = i + 1;
+        last_line = line_number;
+
+        let trimmed = line.trim_end();
+        if trimmed.len() < line.len() {
+            linter.warn(
+                line_number,
+                trimmed.chars().count() + 1,
+                Rule::TrailingWhitespace,
+                "Trailing whitespace",
+            );
+        }
+
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        if !title_seen {
+            title_seen = true;
+            if trimmed.starts_with("# ") {
+                continue;
+            }
+            linter.warn(
+                line_number,
+                1,
+                Rule::MissingTitle,
+                "The page must start with a `# title` line",
+            );
+        }
+
+        if trimmed.starts_with('>') {
+            description_seen = true;
+            if trimmed.contains("More information") {
+                more_information_seen = true;
+            }
+        } else if trimmed.starts_with('-') {
+            examples += 1;
+            if examples == MAX_EXAMPLES + 1 {
+                linter.warn(
+                    line_number,
+                    1,
+                    Rule::ExampleCount,
+                    format!("A page should have at most {} examples", MAX_EXAMPLES),
+                );
+            }
+            if !trimmed.ends_with(':') {
+                linter.warn(
+                    line_number,
+                    trimmed.chars().count(),
+                    Rule::ExampleTextColon,
+                    "Example text should end with a colon",
+                );
+            }
+        } else if trimmed.starts_with('`') && trimmed.len() > 1 && trimmed.ends_with('`') {
+            linter.check_placeholders(line_number, &trimmed[1..trimmed.len() - 1], 1);
+        } else if !trimmed.starts_with('#') {
+            linter.warn(
+                line_number,
+                1,
+                Rule::CodeBackticks,
+                "Example code should be wrapped in backticks",
+            );
+        }
+    }
+
+    if !description_seen {
+        linter.warn(
+            1,
+            1,
+            Rule::MissingDescription,
+            "The page must have a `> description`",
+        );
+    } else if !more_information_seen {
+        linter.warn(
+            1,
+            1,
+            Rule::MissingMoreInformation,
+            "The description should contain a `More information: <url>.` line",
+        );
+    }
+    if examples == 0 {
+        linter.warn(
+            last_line.max(1),
+            1,
+            Rule::ExampleCount,
+            "A page should have at least one example",
+        );
+    }
+
+    linter
+        .warnings
+        .sort_by_key(|warning| (warning.line, warning.column));
+    linter.warnings
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lint_page, Rule};
+
+    const VALID_PAGE: &str = "# tar\n\n\
+        > Archiving utility.\n\
+        > More information: <https://www.gnu.org/software/tar>.\n\n\
+        - Create an archive from files:\n\n\
+        `tar cf {{target.tar}} {{file1 file2 file3}}`\n";
+
+    fn rules(content: &str) -> Vec<(usize, usize, Rule)> {
+        lint_page(content)
+            .into_iter()
+            .map(|warning| (warning.line, warning.column, warning.rule))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_page() {
+        assert_eq!(lint_page(VALID_PAGE), vec![]);
+    }
+
+    #[test]
+    fn test_missing_title_and_description() {
+        assert_eq!(
+            rules("- Example:\n\n`cmd`\n"),
+            vec![(1, 1, Rule::MissingTitle), (1, 1, Rule::MissingDescription)]
+        );
+    }
+
+    #[test]
+    fn test_missing_more_information() {
+        assert_eq!(
+            rules("# tar\n\n> Archiving utility.\n\n- Example:\n\n`tar`\n"),
+            vec![(1, 1, Rule::MissingMoreInformation)]
+        );
+    }
+
+    #[test]
+    fn test_examples() {
+        let page = VALID_PAGE.replace(
+            "- Create an archive from files:\n\n`tar cf",
+            "- Create an archive from files \n\ntar cf",
+        );
+        assert_eq!(
+            rules(&page),
+            vec![
+                (6, 30, Rule::ExampleTextColon),
+                (6, 31, Rule::TrailingWhitespace),
+                (8, 1, Rule::CodeBackticks)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_placeholders() {
+        let page = VALID_PAGE.replace(
+            "`tar cf {{target.tar}} {{file1 file2 file3}}`",
+            "`tar {{}} }} {{a {{b}} {{c`",
+        );
+        assert_eq!(
+            rules(&page),
+            vec![
+                (8, 6, Rule::MalformedPlaceholder),
+                (8, 11, Rule::MalformedPlaceholder),
+                (8, 18, Rule::MalformedPlaceholder),
+                (8, 24, Rule::MalformedPlaceholder)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_example_count() {
+        assert_eq!(
+            rules("# tar\n\n> Archiving utility.\n> More information: <https://x>.\n"),
+            vec![(4, 1, Rule::ExampleCount)]
+        );
+        let mut page = VALID_PAGE.to_string();
+        for _ in 0..8 {
+            page.push_str("\n- Another example:\n\n`tar`\n");
+        }
+        assert_eq!(rules(&page), vec![(38, 1, Rule::ExampleCount)]);
+    }
+}
@@ -0,0 +1,323 @@
+//! Detailed listings of the available pages.
+
+use std::io::Write;
+
+use serde_derive::Serialize;
+
+use crate::alias::alias_target;
+use crate::error::TealdeerError::{self, WriteError};
+use crate::source::{PageContent, SourceChain};
+use crate::tokenizer::Tokenizer;
+use crate::types::{LineType, PageInfo};
+
+/// The platforms to list pages for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlatformFilter {
+    /// The pages of a platform (if any) and `common`
+    WithCommon(Option<String>),
+    /// Only the pages of a single platform directory
+    Only(String),
+    /// The pages of all platforms
+    All,
+}
+
+/// Which pages to list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageFilter {
+    pub platforms: PlatformFilter,
+    /// The language of the pages (`en` for the `pages` directory)
+    pub language: String,
+}
+
+impl PageFilter {
+    /// List the English pages of a platform and `common`.
+    pub fn new(platform: Option<&str>) -> Self {
+        Self {
+            platforms: PlatformFilter::WithCommon(platform.map(String::from)),
+            language: "en".into(),
+        }
+    }
+
+    /// Return the platform to look pages up for.
+    fn platform(&self) -> Option<&str> {
+        match &self.platforms {
+            PlatformFilter::WithCommon(platform) => platform.as_deref(),
+            PlatformFilter::Only(platform) => Some(platform),
+            PlatformFilter::All => None,
+        }
+    }
+
+    /// Return whether a page is listed.
+    ///
+    /// Custom pages have neither a platform nor a language. They are listed
+    /// unless a single platform or another language than English is requested.
+    pub fn matches(&self, info: &PageInfo) -> bool {
+        if info.custom {
+            return self.language == "en" && !matches!(self.platforms, PlatformFilter::Only(_));
+        }
+        let platform = info.platform.as_deref().unwrap_or("");
+        let platform_matches = match &self.platforms {
+            PlatformFilter::WithCommon(filter) => {
+                platform == "common" || filter.as_deref() == Some(platform)
+            }
+            PlatformFilter::Only(filter) => filter == platform,
+            PlatformFilter::All => true,
+        };
+        platform_matches && info.language.as_deref() == Some(self.language.as_str())
+    }
+}
+
+/// Return the sorted names of the pages that match the filter.
+pub fn list_pages(chain: &SourceChain, filter: &PageFilter) -> Result<Vec<String>, TealdeerError> {
+    let mut pages: Vec<String> = chain
+        .entries(Some(&filter.language))?
+        .into_iter()
+        .filter(|(_, info)| filter.matches(info))
+        .map(|(name, _)| name)
+        .collect();
+    pages.sort();
+    pages.dedup();
+    Ok(pages)
+}
+
+/// A page in a detailed listing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PageSummary {
+    pub name: String,
+    /// The first description line of the page
+    pub description: Option<String>,
+    /// The platforms the page is available for, sorted
+    pub platforms: Vec<String>,
+    /// The languages the page is available in, sorted
+    pub languages: Vec<String>,
+    /// Whether the page is replaced by a custom page
+    pub custom: bool,
+    /// Whether a custom patch is appended to the page
+    pub patched: bool,
+    /// The page this page is an alias of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+}
+
+/// Return the first description line of a page and the page it is an alias
+/// of, if any.
+fn describe(
+    chain: &SourceChain,
+    name: &str,
+    platform: Option<&str>,
+    languages: &[String],
+) -> (Option<String>, Option<String>) {
+    let page = match chain.find_page(name, platform, languages) {
+        Some(page) => page,
+        None => return (None, None),
+    };
+    let reader = match page.contents().next().map(PageContent::reader) {
+        Some(Ok(reader)) => reader,
+        _ => return (None, None),
+    };
+    // The description comes first, and a page with a second example is no
+    // alias page, so the rest of the page doesn't have to be parsed
+    let mut tokenizer = Tokenizer::new(reader);
+    let mut tokens = Vec::new();
+    let mut examples = 0;
+    while let Ok(Some(token)) = tokenizer.next_token() {
+        if let LineType::ExampleCode(_) = token {
+            examples += 1;
+        }
+        tokens.push(token);
+        if examples > 1 {
+            break;
+        }
+    }
+    let description = tokens.iter().find_map(|token| match token {
+        LineType::Description(text) => Some(text.clone()),
+        _ => None,
+    });
+    let alias = alias_target(&tokens, page.page_info().platform.as_deref());
+    (description, alias.map(|alias| alias.name))
+}
+
+/// Summarize the pages that match the filter. The descriptions are taken
+/// from the pages in the first of the given languages that has them.
+pub fn summarize_pages(
+    chain: &SourceChain,
+    filter: &PageFilter,
+    languages: &[String],
+) -> Result<Vec<PageSummary>, TealdeerError> {
+    // The pages are sorted by name, so that they can be found by binary search
+    let mut summaries: Vec<PageSummary> = list_pages(chain, filter)?
+        .into_iter()
+        .map(|name| PageSummary {
+            name,
+            ..PageSummary::default()
+        })
+        .collect();
+
+    for (name, info) in chain.entries(None)? {
+        let summary = match summaries.binary_search_by(|summary| summary.name.cmp(&name)) {
+            Ok(i) => &mut summaries[i],
+            Err(_) => continue,
+        };
+        summary.custom |= info.custom;
+        summary.platforms.extend(info.platform);
+        summary.languages.extend(info.language);
+    }
+
+    for summary in &mut summaries {
+        summary.platforms.sort();
+        summary.platforms.dedup();
+        summary.languages.sort();
+        summary.languages.dedup();
+        summary.patched = !summary.custom && chain.has_patch(&summary.name);
+        let (description, alias) = describe(chain, &summary.name, filter.platform(), languages);
+        summary.description = description;
+        summary.alias = alias;
+    }
+
+    Ok(summaries)
+}
+
+/// Write the summaries as aligned columns: name, platforms, languages and
+/// description. Custom, patched and alias pages are marked before the
+/// description.
+pub fn write_table(writer: &mut dyn Write, summaries: &[PageSummary]) -> Result<(), TealdeerError> {
+    let rows: Vec<[String; 4]> = summaries
+        .iter()
+        .map(|summary| {
+            let mut marker = if summary.custom {
+                "(custom) ".to_string()
+            } else if summary.patched {
+                "(patched) ".to_string()
+            } else {
+                String::new()
+            };
+            if let Some(alias) = &summary.alias {
+                marker = format!("{}(alias of {}) ", marker, alias);
+            }
+            [
+                summary.name.clone(),
+                summary.platforms.join(","),
+                summary.languages.join(","),
+                format!("{}{}", marker, summary.description.as_deref().unwrap_or("")),
+            ]
+        })
+        .collect();
+
+    let width = |column: usize| {
+        rows.iter()
+            .map(|row| row[column].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let widths = [width(0), width(1), width(2)];
+    for row in &rows {
+        let line = format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+        );
+        writeln!(writer, "{}", line.trim_end()).map_err(WriteError)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        list_pages, summarize_pages, write_table, PageFilter, PageSummary, PlatformFilter,
+    };
+    use crate::source::{MemorySource, SourceChain};
+
+    fn chain() -> SourceChain {
+        SourceChain::new()
+            .with_source(MemorySource::new().with_patch("tar", "- Patched:"))
+            .with_source(
+                MemorySource::new()
+                    .with_page("en", "common", "tar", "# tar\n\n> Archiver.\n> More.\n")
+                    .with_page("de", "common", "tar", "# tar\n\n> Archivierer.\n")
+                    .with_page("en", "linux", "tar", "# tar\n\n> Linux archiver.\n")
+                    .with_page("en", "osx", "brew", "# brew\n\n> Packages.\n")
+                    .with_page("de", "linux", "apt", "# apt\n\n> Pakete.\n")
+                    .with_page("en", "common", "ls", "# ls\n"),
+            )
+    }
+
+    #[test]
+    fn test_list_pages_filter() {
+        let list = |platforms: PlatformFilter, language: &str| {
+            let filter = PageFilter {
+                platforms,
+                language: language.into(),
+            };
+            list_pages(&chain(), &filter).unwrap()
+        };
+        assert_eq!(
+            list(PlatformFilter::WithCommon(Some("osx".into())), "en"),
+            vec!["brew", "ls", "tar"]
+        );
+        assert_eq!(
+            list(PlatformFilter::Only("linux".into()), "en"),
+            vec!["tar"]
+        );
+        assert_eq!(list(PlatformFilter::All, "en"), vec!["brew", "ls", "tar"]);
+        assert_eq!(list(PlatformFilter::All, "de"), vec!["apt", "tar"]);
+        assert_eq!(list(PlatformFilter::WithCommon(None), "de"), vec!["tar"]);
+    }
+
+    #[test]
+    fn test_summarize_and_write_table() {
+        let summaries =
+            summarize_pages(&chain(), &PageFilter::new(Some("osx")), &["en".into()]).unwrap();
+        assert_eq!(
+            summaries[2],
+            PageSummary {
+                name: "tar".into(),
+                description: Some("Archiver.".into()),
+                platforms: vec!["common".into(), "linux".into()],
+                languages: vec!["de".into(), "en".into()],
+                custom: false,
+                patched: true,
+                alias: None,
+            }
+        );
+
+        let mut output = Vec::new();
+        write_table(&mut output, &summaries).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "brew  osx           en     Packages.\n\
+             ls    common        en\n\
+             tar   common,linux  de,en  (patched) Archiver.\n"
+        );
+    }
+
+    #[test]
+    fn test_alias_marker() {
+        let chain = SourceChain::new().with_source(
+            MemorySource::new()
+                .with_page("en", "common", "ls", "# ls\n\n> Lister.\n")
+                .with_page(
+                    "en",
+                    "common",
+                    "dir",
+                    "# dir\n\n> This command is an alias of `ls`.\n\n- Original:\n\n`tldr ls`\n",
+                ),
+        );
+        let summaries = summarize_pages(&chain, &PageFilter::new(None), &["en".into()]).unwrap();
+        assert_eq!(summaries[0].alias.as_deref(), Some("ls"));
+        assert_eq!(summaries[1].alias, None);
+
+        let mut output = Vec::new();
+        write_table(&mut output, &summaries).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "dir  common  en  (alias of ls) This command is an alias of `ls`.\n\
+             ls   common  en  Lister.\n"
+        );
+    }
+}
@@ -14,113 +14,543 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::too_many_lines)]
 
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
 use std::iter;
 use std::path::PathBuf;
 use std::process;
-use std::{env, io::Write};
+use std::{
+    env,
+    io::{self, Read, Write},
+};
 
 use ansi_term::{Color, Style};
-use app_dirs::AppInfo;
 use atty::Stream;
 use docopt::Docopt;
 #[cfg(not(target_os = "windows"))]
 use pager::Pager;
 use serde_derive::Deserialize;
+use serde_json::json;
 
-mod cache;
-mod config;
+#[cfg(feature = "browse")]
+mod browse;
 mod dedup;
-mod error;
-mod formatter;
-mod tokenizer;
-mod types;
+mod report;
+mod watch;
 
-use crate::cache::{Cache, PageLookupResult};
-use crate::config::{get_config_dir, get_config_path, make_default_config, Config, MAX_CACHE_AGE};
 use crate::dedup::Dedup;
-use crate::error::TealdeerError::ConfigError;
-use crate::formatter::print_lines;
-use crate::tokenizer::Tokenizer;
-use crate::types::{ColorOptions, OsType};
-
-const NAME: &str = "tealdeer";
-const APP_INFO: AppInfo = AppInfo {
-    name: NAME,
-    author: NAME,
+use crate::report::{ErrorFormat, Level, Reporter};
+use crate::watch::watch_pages;
+use tealdeer::internal::{
+    canonicalize, convert_v2, coverage_report, get_config_dir, get_config_path, lint_page,
+    list_pages, make_default_config, print_paired, resolve_aliases, suggest_pages, summarize_pages,
+    write_coverage_table, write_table, AliasMode, AliasTarget, ColorOptions, OutputFormat,
+    PageFilter, PlatformFilter, StyleConfig, MAX_CACHE_AGE,
+};
+use tealdeer::TealdeerError::{self, WriteError};
+use tealdeer::{
+    page_outline, render_page, Cache, ColorDepth, CommandsRenderer, Config, JsonRenderer, OsType,
+    PageContent, PageInfo, PageLookupResult, RawRenderer, Renderer, TerminalRenderer, Tokenizer,
+    ARCHIVE_URL, NAME,
 };
+
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const USAGE: &str = include_str!("usage.docopt");
-const ARCHIVE_URL: &str = "https://github.com/tldr-pages/tldr/archive/master.tar.gz";
 #[cfg(not(target_os = "windows"))]
 const PAGER_COMMAND: &str = "less -R";
+/// The platform directories of the pages.
+const PLATFORMS: &[&str] = &["linux", "osx", "sunos", "windows", "common"];
 
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Deserialize)]
 struct Args {
     arg_command: Option<Vec<String>>,
+    arg_file: Vec<String>,
     flag_help: bool,
     flag_version: bool,
     flag_list: bool,
-    flag_render: Option<String>,
+    flag_long: bool,
+    flag_coverage: bool,
+    flag_browse: bool,
+    flag_translations: bool,
+    flag_paired: bool,
+    flag_render: bool,
+    flag_watch: bool,
+    flag_lint: bool,
+    flag_fmt: bool,
+    flag_convert: bool,
+    flag_check: bool,
+    flag_in_place: bool,
     flag_os: Option<OsType>,
+    flag_platform: Option<String>,
     flag_update: bool,
     flag_clear_cache: bool,
     flag_pager: bool,
     flag_quiet: bool,
+    flag_error_format: ErrorFormat,
     flag_show_paths: bool,
     flag_config_path: bool,
     flag_seed_config: bool,
     flag_markdown: bool,
+    flag_format: Option<OutputFormat>,
     flag_color: ColorOptions,
+    flag_theme: Option<String>,
+    flag_color_depth: Option<ColorDepth>,
     flag_language: Option<String>,
 }
 
-/// Print page by path
+/// Check the combinations of arguments that the usage patterns can't rule out,
+/// e.g. `tldr --lint` without files matches the bare `tldr [options]` pattern.
+fn check_args(args: &Args) -> Result<(), &'static str> {
+    let needs_files = args.flag_render || args.flag_lint || args.flag_fmt || args.flag_convert;
+    if needs_files && args.arg_file.is_empty() {
+        return Err("Missing <file> arguments");
+    }
+    if args.flag_check && args.flag_in_place {
+        return Err("--check and --in-place can't be used together");
+    }
+    if args.flag_watch && !args.flag_render {
+        return Err("--watch can only be used with --render");
+    }
+    Ok(())
+}
+
+/// Print page by path. In the terminal format, examples that mention any of
+/// the `highlight_words` are highlighted.
 fn print_page(
     page: &PageLookupResult,
-    enable_markdown: bool,
+    format: OutputFormat,
     config: &Config,
-) -> Result<(), String> {
+    highlight_words: &[String],
+) -> Result<(), TealdeerError> {
     let stdout = std::io::stdout();
     let mut handle = stdout.lock();
 
-    for path in page.paths() {
-        let file = File::open(path).map_err(|msg| format!("Could not open file: {}", msg))?;
-        let reader = BufReader::new(file);
+    let mut renderer: Box<dyn Renderer> = match format {
+        OutputFormat::Terminal => {
+            Box::new(TerminalRenderer::new(config).with_highlight(highlight_words))
+        }
+        OutputFormat::Plain => Box::new(TerminalRenderer::plain(config)),
+        // The raw markdown of the page and patch is printed as it is
+        OutputFormat::Markdown => Box::new(RawRenderer),
+        OutputFormat::Json => Box::new(JsonRenderer::default()),
+        OutputFormat::Commands => Box::new(CommandsRenderer),
+    };
+    render_page(&mut *renderer, &mut handle, page)?;
+
+    handle.flush().map_err(WriteError)?;
+
+    Ok(())
+}
+
+/// Print the translations of a page and exit.
+///
+/// With `paired`, the terminal formats show the translations side by side.
+/// Otherwise they are printed one after another, each with a header that
+/// shows its language in the terminal formats.
+fn print_translations_and_exit(
+    pages: &[PageLookupResult],
+    paired: bool,
+    format: OutputFormat,
+    config: &Config,
+    reporter: Reporter,
+) {
+    // The header shows the language of each translation
+    let mut config = config.clone();
+    config.display.show_title = true;
+    let result = match format {
+        OutputFormat::Terminal | OutputFormat::Plain if paired => {
+            if format == OutputFormat::Plain {
+                config.style = StyleConfig::default();
+            }
+            pages
+                .iter()
+                .map(page_outline)
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|outlines| {
+                    let stdout = io::stdout();
+                    let mut handle = stdout.lock();
+                    print_paired(&mut handle, &outlines, &config)
+                })
+        }
+        _ => pages
+            .iter()
+            .try_for_each(|page| print_page(page, format, &config, &[])),
+    };
+    if let Err(e) = result {
+        reporter.error("", &e);
+        process::exit(e.exit_code());
+    }
+    process::exit(0);
+}
+
+/// Follow the aliases of a page as configured and return the pages to print.
+///
+/// Unless `quietly` is set, a note is shown for every alias that is followed.
+/// If the aliases form a cycle, only the requested page is shown.
+fn resolve_alias_pages(
+    name: &str,
+    page: PageLookupResult,
+    find: impl Fn(&AliasTarget) -> Option<PageLookupResult>,
+    mode: AliasMode,
+    quietly: bool,
+    reporter: Reporter,
+) -> Vec<PageLookupResult> {
+    if mode == AliasMode::Stub {
+        return vec![page];
+    }
+    let resolved = resolve_aliases(name, page, find);
+    if let Some(ref cycle) = resolved.cycle {
+        let names: Vec<&str> = resolved
+            .pages
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain(iter::once(cycle.as_str()))
+            .collect();
+        reporter.report(
+            Level::Warning,
+            "alias_cycle",
+            &format!("The aliases form a cycle: {}", names.join(" -> ")),
+            Some(json!({ "pages": names })),
+        );
+        let (_, page) = resolved.pages.into_iter().next().unwrap();
+        return vec![page];
+    }
+
+    if mode == AliasMode::Follow && !quietly {
+        for pair in resolved.pages.windows(2) {
+            let (alias, target) = (&pair[0].0, &pair[1].0);
+            reporter.report(
+                Level::Info,
+                "alias",
+                &format!("`{}` is an alias of `{}`.", alias, target),
+                Some(json!({ "page": alias, "target": target })),
+            );
+        }
+    }
+    let mut pages: Vec<PageLookupResult> =
+        resolved.pages.into_iter().map(|(_, page)| page).collect();
+    if mode == AliasMode::Follow {
+        pages.drain(..pages.len() - 1);
+    }
+    pages
+}
+
+/// Search for the page of a command given as words. If there is none,
+/// trailing words are dropped until a page is found, e.g. `git commit amend`,
+/// then `git commit`, then `git`. Hyphens within a word are kept. Return the
+/// name of the page that was found and the number of words it consists of.
+fn find_page_with_fallback(
+    words: &[String],
+    find: impl Fn(&str) -> Option<PageLookupResult>,
+) -> Option<(String, usize, PageLookupResult)> {
+    (1..=words.len()).rev().find_map(|count| {
+        let name = words[..count].join("-");
+        find(&name).map(|page| (name, count, page))
+    })
+}
+
+/// Split a comma separated list of languages, e.g. `de,en`.
+fn split_languages(languages: &str) -> Vec<String> {
+    languages
+        .split(',')
+        .map(str::trim)
+        .filter(|language| !language.is_empty())
+        .map(String::from)
+        .collect()
+}
 
-        if enable_markdown {
-            // Print the raw markdown of the file.
-            for line in reader.lines() {
-                writeln!(handle, "{}", line.unwrap())
-                    .map_err(|_| "Could not write to stdout".to_string())?;
+/// Render local page files and exit. A file name of `-` reads the page from stdin.
+///
+/// If a page cannot be rendered, the remaining pages are rendered anyway and
+/// the exit code is that of the first error.
+fn render_pages_and_exit(
+    files: &[String],
+    format: OutputFormat,
+    config: &Config,
+    reporter: Reporter,
+) {
+    let mut exit_code = 0;
+    for file in files {
+        let page = if file == "-" {
+            let mut content = String::new();
+            if let Err(e) = io::stdin().read_to_string(&mut content) {
+                reporter.report(
+                    Level::Error,
+                    "read_error",
+                    &format!("Could not read stdin: {}", e),
+                    Some(json!({ "file": file })),
+                );
+                if exit_code == 0 {
+                    exit_code = 1;
+                }
+                continue;
             }
+            PageLookupResult::with_content(
+                PageContent::Memory {
+                    name: "<stdin>".into(),
+                    content,
+                },
+                PageInfo::default(),
+            )
         } else {
-            // Create tokenizer and print output
-            let mut tokenizer = Tokenizer::new(reader);
-            print_lines(&mut handle, &mut tokenizer, &config)
-                .map_err(|e| format!("Could not write to stdout: {}", e.message()))?;
+            PageLookupResult::with_page(PathBuf::from(file))
         };
+        if let Err(e) = print_page(&page, format, config, &[]) {
+            reporter.error(&format!("Could not render {}", file), &e);
+            if exit_code == 0 {
+                exit_code = e.exit_code();
+            }
+        }
     }
+    process::exit(exit_code);
+}
 
-    handle
-        .flush()
-        .map_err(|_| "Could not flush stdout".to_string())?;
+/// Check pages for style guide violations and exit.
+///
+/// The exit code is 1 if there are any violations.
+fn lint_pages_and_exit(files: &[String], reporter: Reporter) {
+    let mut success = true;
+    for file in files {
+        let content = match std::fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                reporter.report(
+                    Level::Error,
+                    "read_error",
+                    &format!("Could not read {}: {}", file, e),
+                    Some(json!({ "file": file })),
+                );
+                success = false;
+                continue;
+            }
+        };
+        for warning in lint_page(&content) {
+            println!("{}:{}", file, warning);
+            success = false;
+        }
+    }
+    if !success {
+        process::exit(1);
+    }
+    process::exit(0);
+}
 
-    Ok(())
+/// Rewrite pages and exit.
+///
+/// By default, the rewritten pages are printed to stdout. With `check`, the
+/// names of pages that would change are printed and the exit code is 1
+/// if there are any. With `in_place`, the pages are overwritten.
+fn rewrite_pages_and_exit<F>(
+    files: &[String],
+    rewrite: F,
+    check: bool,
+    in_place: bool,
+    reporter: Reporter,
+) where
+    F: Fn(&str) -> Result<String, String>,
+{
+    let mut success = true;
+    for file in files {
+        let content = match std::fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(e) => {
+                reporter.report(
+                    Level::Error,
+                    "read_error",
+                    &format!("Could not read {}: {}", file, e),
+                    Some(json!({ "file": file })),
+                );
+                success = false;
+                continue;
+            }
+        };
+        let rewritten = match rewrite(&content) {
+            Ok(rewritten) => rewritten,
+            Err(msg) => {
+                reporter.report(
+                    Level::Error,
+                    "parse_error",
+                    &format!("{}: {}", file, msg),
+                    Some(json!({ "file": file })),
+                );
+                success = false;
+                continue;
+            }
+        };
+        if check {
+            if rewritten != content {
+                println!("{}", file);
+                success = false;
+            }
+        } else if in_place {
+            if rewritten != content {
+                if let Err(e) = std::fs::write(file, rewritten) {
+                    reporter.report(
+                        Level::Error,
+                        "write_error",
+                        &format!("Could not write {}: {}", file, e),
+                        Some(json!({ "file": file })),
+                    );
+                    success = false;
+                }
+            }
+        } else {
+            print!("{}", rewritten);
+        }
+    }
+    if !success {
+        process::exit(1);
+    }
+    process::exit(0);
+}
+
+/// Return which pages to list, based on the `--platform` and `--language` flags.
+fn page_filter(args: &Args, cache: &Cache, reporter: Reporter) -> PageFilter {
+    let platforms = match args.flag_platform.as_deref() {
+        None => PlatformFilter::WithCommon(cache.get_platform_dir().map(String::from)),
+        Some("all") => PlatformFilter::All,
+        Some(platform) if PLATFORMS.contains(&platform) => PlatformFilter::Only(platform.into()),
+        Some(platform) => {
+            reporter.report(
+                Level::Error,
+                "usage",
+                &format!(
+                    "Unknown platform: {} (expected one of {}, all)",
+                    platform,
+                    PLATFORMS.join(", ")
+                ),
+                None,
+            );
+            process::exit(1);
+        }
+    };
+    PageFilter {
+        platforms,
+        language: args
+            .flag_language
+            .as_deref()
+            .and_then(|languages| split_languages(languages).into_iter().next())
+            .unwrap_or_else(|| "en".into()),
+    }
+}
+
+/// List the pages with their descriptions, platforms and languages and exit.
+fn list_pages_long_and_exit(
+    cache: &Cache,
+    filter: &PageFilter,
+    languages: &[String],
+    format: OutputFormat,
+    config: &Config,
+    reporter: Reporter,
+) {
+    let summaries = cache
+        .sources(config.directories.custom_pages_dir.as_deref())
+        .and_then(|sources| summarize_pages(&sources, filter, languages))
+        .unwrap_or_else(|e| {
+            reporter.error("Could not get list of pages", &e);
+            process::exit(e.exit_code());
+        });
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let result = if format == OutputFormat::Json {
+        summaries.iter().try_for_each(|summary| {
+            serde_json::to_writer(&mut handle, summary)
+                .map_err(|e| WriteError(e.into()))
+                .and_then(|_| writeln!(handle).map_err(WriteError))
+        })
+    } else {
+        write_table(&mut handle, &summaries)
+    };
+    if let Err(e) = result {
+        reporter.error("", &e);
+        process::exit(e.exit_code());
+    }
+    process::exit(0);
+}
+
+/// Show the translation coverage of the cache and exit.
+///
+/// If no languages are given, all languages in the cache are compared with English.
+fn show_coverage_and_exit(
+    languages: Option<&[String]>,
+    format: OutputFormat,
+    details: bool,
+    reporter: Reporter,
+) {
+    let report = Cache::source()
+        .and_then(|source| {
+            let languages = match languages {
+                Some(languages) => languages.to_vec(),
+                None => source.languages()?,
+            };
+            coverage_report(&source, &languages, &Cache::translation_hashes()?)
+        })
+        .unwrap_or_else(|e| {
+            reporter.error("Could not compute the translation coverage", &e);
+            process::exit(e.exit_code());
+        });
+
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    let result = if format == OutputFormat::Json {
+        report.iter().try_for_each(|coverage| {
+            serde_json::to_writer(&mut handle, coverage)
+                .map_err(|e| WriteError(e.into()))
+                .and_then(|_| writeln!(handle).map_err(WriteError))
+        })
+    } else {
+        write_coverage_table(&mut handle, &report, details)
+    };
+    if let Err(e) = result {
+        reporter.error("", &e);
+        process::exit(e.exit_code());
+    }
+    process::exit(0);
+}
+
+/// Browse the pages interactively and exit.
+#[cfg(feature = "browse")]
+fn browse_and_exit(cache: &Cache, mut languages: Vec<String>, config: &Config, reporter: Reporter) {
+    // Allow switching to all languages in the cache
+    if let Ok(cache_languages) = Cache::source().and_then(|source| source.languages()) {
+        languages.extend(cache_languages);
+        languages.clear_duplicates();
+    }
+    let result = cache
+        .sources(config.directories.custom_pages_dir.as_deref())
+        .and_then(|sources| browse::browse(sources, config, cache.get_platform_dir(), languages));
+    if let Err(e) = result {
+        reporter.error("Could not browse pages", &e);
+        process::exit(e.exit_code());
+    }
+    process::exit(0);
+}
+
+#[cfg(not(feature = "browse"))]
+fn browse_and_exit(_cache: &Cache, _languages: Vec<String>, _config: &Config, reporter: Reporter) {
+    reporter.report(
+        Level::Error,
+        "feature_disabled",
+        "The --browse flag is not available, tealdeer was built without the `browse` feature",
+        None,
+    );
+    process::exit(1);
 }
 
 /// Set up display pager
 #[cfg(not(target_os = "windows"))]
-fn configure_pager() {
+fn configure_pager(_reporter: Reporter) {
     Pager::with_default_pager(PAGER_COMMAND).setup();
 }
 
 #[cfg(target_os = "windows")]
-fn configure_pager() {
-    eprintln!("Warning: -p / --pager flag not available on Windows!");
+fn configure_pager(reporter: Reporter) {
+    reporter.report(
+        Level::Warning,
+        "pager_unsupported",
+        "Warning: -p / --pager flag not available on Windows!",
+        None,
+    );
 }
 
 fn should_update_cache(args: &Args, config: &Config) -> bool {
@@ -130,7 +560,7 @@ fn should_update_cache(args: &Args, config: &Config) -> bool {
 }
 
 /// Check the cache for freshness
-fn check_cache(args: &Args, enable_styles: bool) {
+fn check_cache(args: &Args, enable_styles: bool, reporter: Reporter) {
     match Cache::last_update() {
         Some(ago) if ago > MAX_CACHE_AGE => {
             if args.flag_quiet {
@@ -144,58 +574,71 @@ fn check_cache(args: &Args, enable_styles: bool) {
                 Style::default()
             };
 
-            eprintln!(
-                "{}",
-                warning_style.paint(format!(
+            let max_age_days = MAX_CACHE_AGE.as_secs() / 24 / 3600;
+            reporter.report_styled(
+                Level::Warning,
+                "stale_cache",
+                &format!(
                     "The cache hasn't been updated for more than {} days.\n\
                          You should probably run `tldr --update` soon.",
-                    MAX_CACHE_AGE.as_secs() / 24 / 3600
-                ))
+                    max_age_days
+                ),
+                Some(
+                    json!({ "age_days": ago.as_secs() / 24 / 3600, "max_age_days": max_age_days }),
+                ),
+                warning_style,
             );
         }
         Some(_) => {}
         None => {
-            eprintln!("Cache not found. Please run `tldr --update`.");
-            process::exit(1);
+            let e = TealdeerError::CacheMissing;
+            reporter.error("", &e);
+            process::exit(e.exit_code());
         }
     };
 }
 
 /// Clear the cache
-fn clear_cache(quietly: bool) {
+fn clear_cache(quietly: bool, reporter: Reporter) {
     Cache::clear().unwrap_or_else(|e| {
-        eprintln!("Could not delete cache: {}", e.message());
-        process::exit(1);
+        reporter.error("Could not delete cache", &e);
+        process::exit(e.exit_code());
     });
     if !quietly {
-        eprintln!("Successfully deleted cache.");
+        reporter.report(
+            Level::Info,
+            "cache_cleared",
+            "Successfully deleted cache.",
+            None,
+        );
     }
 }
 
 /// Update the cache
-fn update_cache(cache: &Cache, quietly: bool) {
+fn update_cache(cache: &Cache, quietly: bool, reporter: Reporter) {
     cache.update().unwrap_or_else(|e| {
-        eprintln!("Could not update cache: {}", e.message());
-        process::exit(1);
+        reporter.error("Could not update cache", &e);
+        process::exit(e.exit_code());
     });
     if !quietly {
-        eprintln!("Successfully updated cache.");
+        reporter.report(
+            Level::Info,
+            "cache_updated",
+            "Successfully updated cache.",
+            None,
+        );
     }
 }
 
 /// Show the config path (DEPRECATED)
-fn show_config_path() {
+fn show_config_path(reporter: Reporter) {
     match get_config_path() {
         Ok((config_file_path, _)) => {
             println!("Config path is: {}", config_file_path.to_str().unwrap());
         }
-        Err(ConfigError(msg)) => {
-            eprintln!("Could not look up config_path: {}", msg);
-            process::exit(1);
-        }
-        Err(_) => {
-            eprintln!("Unknown error");
-            process::exit(1);
+        Err(e) => {
+            reporter.error("Could not look up config_path", &e);
+            process::exit(e.exit_code());
         }
     }
 }
@@ -243,22 +686,21 @@ fn show_paths() {
 }
 
 /// Create seed config file and exit
-fn create_config_and_exit() {
+fn create_config_and_exit(reporter: Reporter) {
     match make_default_config() {
         Ok(config_file_path) => {
-            eprintln!(
-                "Successfully created seed config file here: {}",
-                config_file_path.to_str().unwrap()
+            let path = config_file_path.to_str().unwrap();
+            reporter.report(
+                Level::Info,
+                "config_created",
+                &format!("Successfully created seed config file here: {}", path),
+                Some(json!({ "path": path })),
             );
             process::exit(0);
         }
-        Err(ConfigError(msg)) => {
-            eprintln!("Could not create seed config: {}", msg);
-            process::exit(1);
-        }
-        Err(_) => {
-            eprintln!("Unknown error");
-            process::exit(1);
+        Err(e) => {
+            reporter.error("Could not create seed config", &e);
+            process::exit(e.exit_code());
         }
     }
 }
@@ -305,14 +747,64 @@ fn get_os() -> OsType {
     OsType::Other
 }
 
+/// Return the page directories for a POSIX locale (e.g. `de_DE.UTF-8@euro`)
+/// or a BCP 47 language tag (e.g. `pt-BR`), most specific first.
+///
+/// The codeset and modifier are ignored. Traditional Chinese (`zh_Hant`) is
+/// mapped to `zh_TW`, because that is where the tldr pages keep it. The `C`
+/// and `POSIX` locales have no language.
+fn locale_languages(locale: &str) -> Vec<String> {
+    let locale = locale.split('@').next().unwrap_or("");
+    let locale = locale.split('.').next().unwrap_or("");
+    if locale == "C" || locale == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut subtags = locale.split(['_', '-']);
+    let language = match subtags.next() {
+        Some(language)
+            if (2..=3).contains(&language.len())
+                && language.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            language.to_ascii_lowercase()
+        }
+        _ => return Vec::new(),
+    };
+    let mut script = None;
+    let mut region = None;
+    for subtag in subtags {
+        let is_alphabetic = subtag.chars().all(|c| c.is_ascii_alphabetic());
+        let is_numeric = subtag.chars().all(|c| c.is_ascii_digit());
+        match subtag.len() {
+            4 if is_alphabetic && script.is_none() && region.is_none() => {
+                script = Some(subtag.to_ascii_lowercase());
+            }
+            2 if is_alphabetic && region.is_none() => region = Some(subtag.to_ascii_uppercase()),
+            3 if is_numeric && region.is_none() => region = Some(subtag.to_string()),
+            _ => {}
+        }
+    }
+
+    let mut languages = Vec::new();
+    if let Some(region) = region {
+        languages.push(format!("{}_{}", language, region));
+    }
+    if language == "zh" && script.as_deref() == Some("hant") {
+        languages.push("zh_TW".to_string());
+    }
+    languages.push(language);
+    languages.clear_duplicates();
+    languages
+}
+
 fn get_languages(env_lang: Option<&str>, env_language: Option<&str>) -> Vec<String> {
     // Language list according to
     // https://github.com/tldr-pages/tldr/blob/master/CLIENT-SPECIFICATION.md#language
 
-    if env_lang.is_none() {
-        return vec!["en".to_string()];
-    }
-    let env_lang = env_lang.unwrap();
+    let env_lang = match env_lang {
+        Some(env_lang) if !env_lang.is_empty() => env_lang,
+        _ => return vec!["en".to_string()],
+    };
 
     // Create an iterator that contains $LANGUAGE (':' separated list) followed by $LANG (single language)
     let locales = env_language
@@ -320,27 +812,46 @@ fn get_languages(env_lang: Option<&str>, env_language: Option<&str>) -> Vec<Stri
         .split(':')
         .chain(iter::once(env_lang));
 
-    let mut lang_list = Vec::new();
-    for locale in locales {
-        // Language plus country code (e.g. `en_US`)
-        if locale.len() >= 5 && locale.chars().nth(2) == Some('_') {
-            lang_list.push(&locale[..5]);
-        }
-        // Language code only (e.g. `en`)
-        if locale.len() >= 2 && locale != "POSIX" {
-            lang_list.push(&locale[..2]);
-        }
-    }
-
-    lang_list.push("en");
+    let mut lang_list: Vec<String> = locales.flat_map(locale_languages).collect();
+    lang_list.push("en".to_string());
     lang_list.clear_duplicates();
-    lang_list.into_iter().map(str::to_string).collect()
+    lang_list
 }
 
+/// Return the languages from the environment. The locale is taken from the
+/// first variable that is set out of `LC_ALL`, `LC_MESSAGES` and `LANG`.
 fn get_languages_from_env() -> Vec<String> {
-    get_languages(
-        std::env::var("LANG").ok().as_deref(),
-        std::env::var("LANGUAGE").ok().as_deref(),
+    let env_var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+    let env_lang = env_var("LC_ALL")
+        .or_else(|| env_var("LC_MESSAGES"))
+        .or_else(|| env_var("LANG"));
+    get_languages(env_lang.as_deref(), env_var("LANGUAGE").as_deref())
+}
+
+fn get_color_depth(env_colorterm: Option<&str>, env_term: Option<&str>) -> ColorDepth {
+    // There is no standard way to query the color depth, but $COLORTERM is
+    // set by most terminals that support 24-bit colors.
+    if env_colorterm == Some("truecolor") || env_colorterm == Some("24bit") {
+        return ColorDepth::TrueColor;
+    }
+    match env_term {
+        Some(term) if term.contains("truecolor") || term.contains("direct") => {
+            ColorDepth::TrueColor
+        }
+        // Practically all terminals that claim to be a plain xterm support
+        // 256 colors, even if they don't say so. Variants like `xterm-16color`
+        // explicitly advertise fewer colors.
+        Some(term) if term.contains("256color") || term == "xterm" => ColorDepth::Ansi256,
+        // Terminals on Windows usually don't set $TERM, but support 24-bit colors.
+        None if cfg!(target_os = "windows") => ColorDepth::TrueColor,
+        _ => ColorDepth::Ansi16,
+    }
+}
+
+fn get_color_depth_from_env() -> ColorDepth {
+    get_color_depth(
+        std::env::var("COLORTERM").ok().as_deref(),
+        std::env::var("TERM").ok().as_deref(),
     )
 }
 
@@ -360,10 +871,23 @@ fn main() {
         process::exit(0);
     }
 
+    let reporter = Reporter::new(args.flag_error_format);
+
+    if let Err(message) = check_args(&args) {
+        let message = format!("{}\n\n{}", message, USAGE);
+        reporter.report(Level::Error, "usage", &message, None);
+        process::exit(1);
+    }
+
     // Show config file and path, pass through
     if args.flag_config_path {
-        eprintln!("Warning: The --config-path flag is deprecated, use --show-paths instead");
-        show_config_path();
+        reporter.report(
+            Level::Warning,
+            "deprecated_flag",
+            "Warning: The --config-path flag is deprecated, use --show-paths instead",
+            Some(json!({ "flag": "--config-path", "replacement": "--show-paths" })),
+        );
+        show_config_path(reporter);
     }
     if args.flag_show_paths {
         show_paths();
@@ -371,7 +895,7 @@ fn main() {
 
     // Create a basic config and exit
     if args.flag_seed_config {
-        create_config_and_exit();
+        create_config_and_exit(reporter);
     }
 
     // Determine the usage of styles
@@ -395,20 +919,19 @@ fn main() {
     };
 
     // Look up config file, if none is found fall back to default config.
-    let config = match Config::load(enable_styles) {
+    let color_depth = args
+        .flag_color_depth
+        .unwrap_or_else(get_color_depth_from_env);
+    let config = match Config::load(enable_styles, args.flag_theme.as_deref(), color_depth) {
         Ok(config) => config,
-        Err(ConfigError(msg)) => {
-            eprintln!("Could not load config: {}", msg);
-            process::exit(1);
-        }
         Err(e) => {
-            eprintln!("Could not load config: {}", e);
-            process::exit(1);
+            reporter.error("Could not load config", &e);
+            process::exit(e.exit_code());
         }
     };
 
     if args.flag_pager || config.display.use_pager {
-        configure_pager();
+        configure_pager(reporter);
     }
 
     // Specify target OS
@@ -418,44 +941,115 @@ fn main() {
     };
 
     // Initialize cache
-    let cache = Cache::new(ARCHIVE_URL, os);
+    let cache = Cache::new(ARCHIVE_URL, os).with_source_order(config.directories.sources.clone());
 
     // Clear cache, pass through
     if args.flag_clear_cache {
-        clear_cache(args.flag_quiet);
+        clear_cache(args.flag_quiet, reporter);
     }
 
     // Update cache, pass through
     let cache_updated = if should_update_cache(&args, &config) {
-        update_cache(&cache, args.flag_quiet);
+        update_cache(&cache, args.flag_quiet, reporter);
         true
     } else {
         false
     };
 
-    // Render local file and exit
-    if let Some(ref file) = args.flag_render {
-        let path = PageLookupResult::with_page(PathBuf::from(file));
-        if let Err(msg) = print_page(&path, args.flag_markdown, &config) {
-            eprintln!("{}", msg);
-            process::exit(1);
-        } else {
-            process::exit(0);
+    // Lint local files and exit
+    if args.flag_lint {
+        lint_pages_and_exit(&args.arg_file, reporter);
+    }
+
+    // Format local files and exit
+    if args.flag_fmt {
+        let format = |content: &str| {
+            canonicalize(&mut Tokenizer::new(content.as_bytes())).map_err(|e| e.to_string())
         };
+        rewrite_pages_and_exit(
+            &args.arg_file,
+            format,
+            args.flag_check,
+            args.flag_in_place,
+            reporter,
+        );
+    }
+
+    // Convert local files to the current format and exit
+    if args.flag_convert {
+        let convert = |content: &str| {
+            convert_v2(&mut Tokenizer::new(content.as_bytes())).map_err(|e| e.to_string())
+        };
+        rewrite_pages_and_exit(
+            &args.arg_file,
+            convert,
+            args.flag_check,
+            args.flag_in_place,
+            reporter,
+        );
+    }
+
+    // The markdown flag is an alias for the markdown output format
+    let format = if args.flag_markdown {
+        OutputFormat::Markdown
+    } else {
+        args.flag_format.unwrap_or(OutputFormat::Terminal)
+    };
+
+    // Render local files and exit
+    if args.flag_watch {
+        watch_pages(&args.arg_file, format, &config, enable_styles, reporter);
+        process::exit(1);
+    }
+    if args.flag_render {
+        render_pages_and_exit(&args.arg_file, format, &config, reporter);
+    }
+
+    // The languages to look pages up in, in order of preference
+    let flag_languages = args.flag_language.as_deref().map(split_languages);
+    let languages = flag_languages
+        .clone()
+        .or_else(|| Some(config.display.languages.clone()).filter(|l| !l.is_empty()))
+        .unwrap_or_else(get_languages_from_env);
+
+    // Browse pages interactively and exit
+    if args.flag_browse {
+        if !cache_updated {
+            // Check cache for freshness
+            check_cache(&args, enable_styles, reporter);
+        }
+        browse_and_exit(&cache, languages.clone(), &config, reporter);
+    }
+
+    // Show the translation coverage and exit
+    if args.flag_coverage {
+        if !cache_updated {
+            // Check cache for freshness
+            check_cache(&args, enable_styles, reporter);
+        }
+        show_coverage_and_exit(flag_languages.as_deref(), format, args.flag_long, reporter);
     }
 
     // List cached commands and exit
     if args.flag_list {
         if !cache_updated {
             // Check cache for freshness
-            check_cache(&args, enable_styles);
+            check_cache(&args, enable_styles, reporter);
+        }
+
+        let filter = page_filter(&args, &cache, reporter);
+        if args.flag_long {
+            list_pages_long_and_exit(&cache, &filter, &languages, format, &config, reporter);
         }
 
         // Get list of pages
-        let pages = cache.list_pages().unwrap_or_else(|e| {
-            eprintln!("Could not get list of pages: {}", e.message());
-            process::exit(1);
-        });
+        let pages = cache
+            .sources(config.directories.custom_pages_dir.as_deref())
+            .and_then(|sources| list_pages(&sources, &filter))
+            .unwrap_or_else(|e| {
+                reporter.error("Could not get list of pages", &e);
+                process::exit(e.exit_code());
+            });
 
         // Print pages
         println!("{}", pages.join("\n"));
@@ -463,51 +1057,118 @@ fn main() {
     }
 
     // Show command from cache
-    if let Some(ref command) = args.arg_command {
-        let command = command.join("-");
+    if let Some(ref words) = args.arg_command {
+        let command = words.join("-");
 
         if !cache_updated {
             // Check cache for freshness
-            check_cache(&args, enable_styles);
+            check_cache(&args, enable_styles, reporter);
         }
 
-        let languages = args
-            .flag_language
-            .map_or_else(get_languages_from_env, |flag_lang| vec![flag_lang]);
-
         // Search for command in cache
-        if let Some(page) = cache.find_page(
-            &command,
-            &languages,
-            config.directories.custom_pages_dir.as_deref(),
-        ) {
-            if let Err(msg) = print_page(&page, args.flag_markdown, &config) {
-                eprintln!("{}", msg);
-                process::exit(1);
+        let custom_pages_dir = config.directories.custom_pages_dir.as_deref();
+        // Showing the translations side by side implies showing them
+        if args.flag_translations || args.flag_paired {
+            // Without a language list, show all translations in the cache
+            let languages = flag_languages.unwrap_or_else(|| {
+                let mut languages = languages.clone();
+                languages.extend(
+                    Cache::source()
+                        .and_then(|source| source.languages())
+                        .unwrap_or_default(),
+                );
+                languages.clear_duplicates();
+                languages
+            });
+            let pages = cache
+                .find_translations(&command, &languages, custom_pages_dir)
+                .unwrap_or_else(|e| {
+                    reporter.error("Could not search for translations", &e);
+                    process::exit(e.exit_code());
+                });
+            if !pages.is_empty() {
+                print_translations_and_exit(&pages, args.flag_paired, format, &config, reporter);
+            }
+        } else if let Some((name, count, page)) = find_page_with_fallback(words, |name| {
+            cache.find_page(name, &languages, custom_pages_dir)
+        }) {
+            // Highlight the examples that mention the words without a page
+            let dropped_words: Vec<String> = words[count..]
+                .iter()
+                .flat_map(|word| word.split_whitespace())
+                .map(String::from)
+                .collect();
+            if !dropped_words.is_empty() && !args.flag_quiet {
+                reporter.report(
+                    Level::Info,
+                    "page_fallback",
+                    &format!("No page for `{}`, showing `{}` instead.", command, name),
+                    Some(json!({ "page": command, "matched": name })),
+                );
+            }
+            let pages = resolve_alias_pages(
+                &name,
+                page,
+                |target| match target.platform {
+                    // e.g. a `windows` page that refers to the `common` page
+                    Some(ref platform) => cache.sources(custom_pages_dir).ok()?.find_page(
+                        &target.name,
+                        Some(platform),
+                        &languages,
+                    ),
+                    None => cache.find_page(&target.name, &languages, custom_pages_dir),
+                },
+                config.display.aliases,
+                args.flag_quiet,
+                reporter,
+            );
+            for page in &pages {
+                if let Err(e) = print_page(page, format, &config, &dropped_words) {
+                    reporter.error("", &e);
+                    process::exit(e.exit_code());
+                }
             }
             process::exit(0);
+        }
+
+        let e = TealdeerError::PageNotFound(command.clone());
+        if args.flag_quiet {
+            process::exit(e.exit_code());
+        }
+        if reporter.is_text() {
+            eprintln!("{}", e);
+            eprintln!("Try updating with `tldr --update`, or submit a pull request to:");
+            eprintln!("https://github.com/tldr-pages/tldr");
         } else {
-            if !args.flag_quiet {
-                eprintln!("Page {} not found in cache", &command);
-                eprintln!("Try updating with `tldr --update`, or submit a pull request to:");
-                eprintln!("https://github.com/tldr-pages/tldr");
-            }
-            process::exit(1);
+            // Suggest similar pages to tools consuming the JSON output
+            let suggestions = cache
+                .list_pages()
+                .map(|pages| suggest_pages(&command, &pages))
+                .unwrap_or_default();
+            let mut details = serde_json::Map::new();
+            details.insert("page".into(), command.into());
+            details.insert("suggestions".into(), suggestions.into());
+            reporter.error_with_details("", &e, details);
         }
+        process::exit(e.exit_code());
     }
 
     // Some flags can be run without a command.
     if !(args.flag_update || args.flag_clear_cache || args.flag_config_path || args.flag_show_paths)
     {
-        eprintln!("{}", USAGE);
+        reporter.report(Level::Error, "usage", USAGE, None);
         process::exit(1);
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{get_languages, Args, OsType, USAGE};
+    use crate::{
+        check_args, find_page_with_fallback, get_color_depth, get_languages, split_languages, Args,
+        ColorDepth, OsType, OutputFormat, PageLookupResult, USAGE,
+    };
     use docopt::{Docopt, Error};
+    use std::path::PathBuf;
 
     fn test_helper(argv: &[&str]) -> Result<Args, Error> {
         Docopt::new(USAGE).and_then(|d| d.argv(argv.iter()).deserialize())
@@ -520,12 +1181,169 @@ mod test {
         assert_eq!(OsType::Linux, os);
     }
 
+    #[test]
+    fn test_docopt_lint_files() {
+        let argv = vec!["cp", "--lint", "a.md", "b.md"];
+        let args = test_helper(&argv).unwrap();
+        assert!(args.flag_lint);
+        assert_eq!(args.arg_file, vec!["a.md", "b.md"]);
+        assert_eq!(args.arg_command, None);
+        assert_eq!(check_args(&args), Ok(()));
+    }
+
+    #[test]
+    fn test_check_args_missing_files() {
+        for flag in &["--lint", "--fmt", "--convert", "--render"] {
+            let args = test_helper(&["cp", flag]).unwrap();
+            assert!(check_args(&args).is_err(), "{} without files", flag);
+        }
+        let args = test_helper(&["cp", "--update"]).unwrap();
+        assert_eq!(check_args(&args), Ok(()));
+    }
+
+    #[test]
+    fn test_check_args_check_in_place() {
+        let args = test_helper(&["cp", "--fmt", "--check", "--in-place", "a.md"]).unwrap();
+        assert!(check_args(&args).is_err());
+        let args = test_helper(&["cp", "--fmt", "--check", "a.md"]).unwrap();
+        assert_eq!(check_args(&args), Ok(()));
+    }
+
+    #[test]
+    fn test_check_args_watch() {
+        let args = test_helper(&["cp", "--watch", "tar"]).unwrap();
+        assert!(check_args(&args).is_err());
+        let args = test_helper(&["cp", "--render", "--watch", "a.md"]).unwrap();
+        assert_eq!(check_args(&args), Ok(()));
+    }
+
+    #[test]
+    fn test_docopt_render_files() {
+        let argv = vec!["cp", "-f", "a.md", "-"];
+        let args = test_helper(&argv).unwrap();
+        assert!(args.flag_render);
+        assert_eq!(args.arg_file, vec!["a.md", "-"]);
+        assert_eq!(args.arg_command, None);
+    }
+
+    #[test]
+    fn test_docopt_render_watch() {
+        let argv = vec!["cp", "--render", "--watch", "a.md"];
+        let args = test_helper(&argv).unwrap();
+        assert!(args.flag_render);
+        assert!(args.flag_watch);
+        assert_eq!(args.arg_file, vec!["a.md"]);
+    }
+
+    #[test]
+    fn test_find_page_with_fallback() {
+        let find = |name: &str| {
+            if name == "git" || name == "git-commit" {
+                Some(PageLookupResult::with_page(PathBuf::from(name)))
+            } else {
+                None
+            }
+        };
+        let found = |words: &[&str]| {
+            let words: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+            find_page_with_fallback(&words, find).map(|(name, count, _)| (name, count))
+        };
+        assert_eq!(
+            found(&["git", "commit", "amend"]),
+            Some(("git-commit".into(), 2))
+        );
+        assert_eq!(found(&["git-commit"]), Some(("git-commit".into(), 1)));
+        assert_eq!(found(&["git", "push", "force"]), Some(("git".into(), 1)));
+        assert_eq!(found(&["svn", "commit"]), None);
+        // Hyphens typed within a word are not split
+        assert_eq!(found(&["git-commitx"]), None);
+        assert_eq!(found(&["git-commit-amend"]), None);
+    }
+
+    #[test]
+    fn test_docopt_translations() {
+        let argv = vec!["cp", "-t", "--paired", "--language", "de, en,", "tar"];
+        let args = test_helper(&argv).unwrap();
+        assert!(args.flag_translations);
+        assert!(args.flag_paired);
+        assert_eq!(
+            args.flag_language.as_deref().map(split_languages),
+            Some(vec!["de".to_string(), "en".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_docopt_fmt_files() {
+        let argv = vec!["cp", "--fmt", "--check", "a.md"];
+        let args = test_helper(&argv).unwrap();
+        assert!(args.flag_fmt);
+        assert!(args.flag_check);
+        assert!(!args.flag_in_place);
+        assert_eq!(args.arg_file, vec!["a.md"]);
+    }
+
+    #[test]
+    fn test_docopt_convert_files() {
+        let argv = vec!["cp", "--convert", "--in-place", "a.md", "b.md"];
+        let args = test_helper(&argv).unwrap();
+        assert!(args.flag_convert);
+        assert!(args.flag_in_place);
+        assert_eq!(args.arg_file, vec!["a.md", "b.md"]);
+    }
+
+    #[test]
+    fn test_docopt_format() {
+        let argv = vec!["cp", "--format", "json", "tar"];
+        let format = test_helper(&argv).unwrap().flag_format;
+        assert_eq!(format, Some(OutputFormat::Json));
+
+        let argv = vec!["cp", "--format", "yaml", "tar"];
+        assert!(test_helper(&argv).is_err());
+    }
+
     #[test]
     fn test_docopt_expect_error() {
         let argv = vec!["cp", "--os", "lindows"];
         assert!(!test_helper(&argv).is_ok());
     }
 
+    #[test]
+    fn test_docopt_color_depth() {
+        let argv = vec!["cp", "--color-depth", "256"];
+        let depth = test_helper(&argv).unwrap().flag_color_depth;
+        assert_eq!(Some(ColorDepth::Ansi256), depth);
+        let argv = vec!["cp", "--color-depth", "truecolor"];
+        let depth = test_helper(&argv).unwrap().flag_color_depth;
+        assert_eq!(Some(ColorDepth::TrueColor), depth);
+    }
+
+    #[test]
+    fn test_color_depth_detection() {
+        assert_eq!(
+            get_color_depth(Some("truecolor"), Some("xterm")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(get_color_depth(Some("24bit"), None), ColorDepth::TrueColor);
+        assert_eq!(
+            get_color_depth(None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(get_color_depth(None, Some("xterm")), ColorDepth::Ansi256);
+        assert_eq!(
+            get_color_depth(None, Some("xterm-color")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(
+            get_color_depth(None, Some("xterm-16color")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(get_color_depth(None, Some("linux")), ColorDepth::Ansi16);
+        assert_eq!(
+            get_color_depth(Some(""), Some("screen")),
+            ColorDepth::Ansi16
+        );
+    }
+
     mod language {
         use super::*;
 
@@ -563,6 +1381,34 @@ mod test {
             assert_eq!(lang_list, vec!["en"]);
         }
 
+        #[test]
+        fn codeset_and_modifier() {
+            let lang_list = get_languages(Some("de_DE.UTF-8@euro"), None);
+            assert_eq!(lang_list, vec!["de_DE", "de", "en"]);
+            let lang_list = get_languages(Some("C.UTF-8"), Some("fr_FR.ISO-8859-1"));
+            assert_eq!(lang_list, vec!["fr_FR", "fr", "en"]);
+        }
+
+        #[test]
+        fn bcp47_tags_and_scripts() {
+            let lang_list = get_languages(Some("pt-br"), None);
+            assert_eq!(lang_list, vec!["pt_BR", "pt", "en"]);
+            let lang_list = get_languages(Some("zh_Hant_TW.UTF-8"), None);
+            assert_eq!(lang_list, vec!["zh_TW", "zh", "en"]);
+            let lang_list = get_languages(Some("zh-Hant"), None);
+            assert_eq!(lang_list, vec!["zh_TW", "zh", "en"]);
+            let lang_list = get_languages(Some("es_419"), None);
+            assert_eq!(lang_list, vec!["es_419", "es", "en"]);
+        }
+
+        #[test]
+        fn invalid_and_empty_locales() {
+            let lang_list = get_languages(Some(""), Some("de"));
+            assert_eq!(lang_list, vec!["en"]);
+            let lang_list = get_languages(Some("en_US"), Some("::x:de_AT"));
+            assert_eq!(lang_list, vec!["de_AT", "de", "en_US", "en"]);
+        }
+
         #[test]
         fn no_duplicates() {
             let lang_list = get_languages(Some("de"), Some("fr:de:cn:de"));
@@ -0,0 +1,312 @@
+//! Renderers that write parsed pages in different output formats.
+
+use std::io::{self, BufRead, Write};
+
+use serde_derive::Serialize;
+
+use crate::cache::PageLookupResult;
+use crate::error::TealdeerError::{self, ParseError, WriteError};
+use crate::tokenizer::Tokenizer;
+use crate::types::{LineType, PageInfo};
+
+/// An output backend for pages.
+///
+/// A page is rendered as the main page followed by its patches. Each of them
+/// is rendered by a call to `start`, a call to `render_content` (which calls
+/// `render` for every token) and a call to `end`. When the page is complete,
+/// `finish` is called.
+pub trait Renderer {
+    /// Start rendering the main page, or a patch if `patch_name` is set.
+    fn start(
+        &mut self,
+        _writer: &mut dyn Write,
+        _page_info: &PageInfo,
+        _patch_name: Option<&str>,
+    ) -> Result<(), TealdeerError> {
+        Ok(())
+    }
+
+    /// Render the content of the page or patch with the given file name.
+    ///
+    /// By default, the content is parsed and every token is rendered.
+    fn render_content(
+        &mut self,
+        writer: &mut dyn Write,
+        name: &str,
+        reader: &mut dyn BufRead,
+    ) -> Result<(), TealdeerError> {
+        let mut tokenizer = Tokenizer::new(reader);
+        loop {
+            let token = tokenizer.next_token().map_err(|e| match e {
+                ParseError(msg, source) => {
+                    ParseError(format!("Could not parse {}: {}", name, msg), source)
+                }
+                e => e,
+            })?;
+            match token {
+                Some(token) => self.render(writer, token)?,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Render a token of the page or patch.
+    fn render(&mut self, writer: &mut dyn Write, token: LineType) -> Result<(), TealdeerError>;
+
+    /// Finish rendering the main page or a patch.
+    fn end(&mut self, _writer: &mut dyn Write) -> Result<(), TealdeerError> {
+        Ok(())
+    }
+
+    /// Finish rendering the page, after the main page and all patches.
+    fn finish(&mut self, _writer: &mut dyn Write) -> Result<(), TealdeerError> {
+        Ok(())
+    }
+}
+
+/// Render a page and its patches with the given renderer.
+pub fn render_page(
+    renderer: &mut dyn Renderer,
+    writer: &mut dyn Write,
+    page: &PageLookupResult,
+) -> Result<(), TealdeerError> {
+    for (i, content) in page.contents().enumerate() {
+        let name = content.name();
+        let patch_name = if i > 0 { Some(&*name) } else { None };
+        renderer.start(writer, page.page_info(), patch_name)?;
+        renderer.render_content(writer, &name, &mut content.reader()?)?;
+        renderer.end(writer)?;
+    }
+    renderer.finish(writer)
+}
+
+/// Writes the raw markdown of pages and patches, without parsing them.
+///
+/// Tokens that are rendered individually are written in the standard format.
+#[derive(Debug, Default)]
+pub struct RawRenderer;
+
+impl Renderer for RawRenderer {
+    fn render_content(
+        &mut self,
+        writer: &mut dyn Write,
+        _name: &str,
+        reader: &mut dyn BufRead,
+    ) -> Result<(), TealdeerError> {
+        io::copy(reader, writer).map_err(WriteError)?;
+        Ok(())
+    }
+
+    fn render(&mut self, writer: &mut dyn Write, token: LineType) -> Result<(), TealdeerError> {
+        match token {
+            LineType::Empty => writeln!(writer),
+            LineType::Title(title) => writeln!(writer, "# {}", title),
+            LineType::Description(text) => writeln!(writer, "> {}", text),
+            LineType::ExampleText(text) => writeln!(writer, "- {}", text),
+            LineType::ExampleCode(code) => writeln!(writer, "`{}`", code),
+            LineType::Other(text) => writeln!(writer, "{}", text),
+        }
+        .map_err(WriteError)
+    }
+}
+
+/// Renders only the example commands, one per line.
+#[derive(Debug, Default)]
+pub struct CommandsRenderer;
+
+impl Renderer for CommandsRenderer {
+    fn render(&mut self, writer: &mut dyn Write, token: LineType) -> Result<(), TealdeerError> {
+        if let LineType::ExampleCode(code) = token {
+            writeln!(writer, "{}", code).map_err(WriteError)?;
+        }
+        Ok(())
+    }
+}
+
+/// An example of a page outline.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct OutlineExample {
+    pub description: String,
+    pub command: String,
+}
+
+/// The parts of a page (including its patches), e.g. to compare translations.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct PageOutline {
+    pub name: String,
+    pub platform: Option<String>,
+    pub language: Option<String>,
+    pub custom: bool,
+    pub patched: bool,
+    /// The description lines, joined by newlines
+    pub description: String,
+    pub examples: Vec<OutlineExample>,
+}
+
+/// Collects the outline of a page without writing anything.
+#[derive(Debug, Default)]
+pub struct OutlineRenderer {
+    outline: PageOutline,
+    /// The example text waiting for its example code
+    example_text: Option<String>,
+}
+
+impl OutlineRenderer {
+    /// Return the outline of the rendered page.
+    pub fn into_outline(self) -> PageOutline {
+        self.outline
+    }
+}
+
+impl Renderer for OutlineRenderer {
+    fn start(
+        &mut self,
+        _writer: &mut dyn Write,
+        page_info: &PageInfo,
+        patch_name: Option<&str>,
+    ) -> Result<(), TealdeerError> {
+        if patch_name.is_some() {
+            self.outline.patched = true;
+        } else {
+            self.outline.platform.clone_from(&page_info.platform);
+            self.outline.language.clone_from(&page_info.language);
+            self.outline.custom = page_info.custom;
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, _writer: &mut dyn Write, token: LineType) -> Result<(), TealdeerError> {
+        match token {
+            LineType::Title(title) if self.outline.name.is_empty() => self.outline.name = title,
+            LineType::Description(text) => {
+                if !self.outline.description.is_empty() {
+                    self.outline.description.push('\n');
+                }
+                self.outline.description.push_str(&text);
+            }
+            LineType::ExampleText(text) => self.example_text = Some(text),
+            LineType::ExampleCode(command) => self.outline.examples.push(OutlineExample {
+                description: self.example_text.take().unwrap_or_default(),
+                command,
+            }),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Return the outline of a page and its patches.
+pub fn page_outline(page: &PageLookupResult) -> Result<PageOutline, TealdeerError> {
+    let mut renderer = OutlineRenderer::default();
+    render_page(&mut renderer, &mut io::sink(), page)?;
+    Ok(renderer.into_outline())
+}
+
+/// Renders a page (including its patches) as a single line of JSON.
+#[derive(Debug, Default)]
+pub struct JsonRenderer(OutlineRenderer);
+
+impl Renderer for JsonRenderer {
+    fn start(
+        &mut self,
+        writer: &mut dyn Write,
+        page_info: &PageInfo,
+        patch_name: Option<&str>,
+    ) -> Result<(), TealdeerError> {
+        self.0.start(writer, page_info, patch_name)
+    }
+
+    fn render(&mut self, writer: &mut dyn Write, token: LineType) -> Result<(), TealdeerError> {
+        self.0.render(writer, token)
+    }
+
+    fn finish(&mut self, writer: &mut dyn Write) -> Result<(), TealdeerError> {
+        let outline = std::mem::take(&mut self.0).into_outline();
+        serde_json::to_writer(&mut *writer, &outline).map_err(|e| WriteError(e.into()))?;
+        writeln!(writer).map_err(WriteError)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render_page, CommandsRenderer, JsonRenderer, RawRenderer, Renderer};
+    use crate::cache::PageLookupResult;
+    use crate::source::PageContent;
+    use crate::types::{LineType, PageInfo};
+
+    const PAGE: &str = "tar\n===\n\n> Archiving utility.\n\n\
+        Create an archive:\n\n    tar cf {{target.tar}} {{file}}\n";
+
+    fn page() -> PageLookupResult {
+        PageLookupResult::with_content(
+            PageContent::Memory {
+                name: "tar.md".into(),
+                content: PAGE.into(),
+            },
+            PageInfo {
+                platform: Some("common".into()),
+                language: Some("en".into()),
+                custom: false,
+            },
+        )
+        .with_optional_patch(Some(PageContent::Memory {
+            name: "tar.patch".into(),
+            content: "- Extract an archive:\n\n`tar xf {{source.tar}}`\n".into(),
+        }))
+    }
+
+    fn render(renderer: &mut dyn Renderer) -> String {
+        let mut output = Vec::new();
+        render_page(renderer, &mut output, &page()).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_raw() {
+        assert_eq!(
+            render(&mut RawRenderer),
+            format!(
+                "{}- Extract an archive:\n\n`tar xf {{{{source.tar}}}}`\n",
+                PAGE
+            )
+        );
+    }
+
+    #[test]
+    fn test_raw_tokens() {
+        let mut output = Vec::new();
+        let mut renderer = RawRenderer;
+        for token in vec![
+            LineType::Title("tar".into()),
+            LineType::Empty,
+            LineType::ExampleText("Create an archive:".into()),
+            LineType::ExampleCode("tar cf {{target.tar}}".into()),
+        ] {
+            renderer.render(&mut output, token).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "# tar\n\n- Create an archive:\n`tar cf {{target.tar}}`\n"
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            render(&mut CommandsRenderer),
+            "tar cf {{target.tar}} {{file}}\ntar xf {{source.tar}}\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            render(&mut JsonRenderer::default()),
+            "{\"name\":\"tar\",\"platform\":\"common\",\"language\":\"en\",\
+             \"custom\":false,\"patched\":true,\"description\":\"Archiving utility.\",\
+             \"examples\":[{\"description\":\"Create an archive:\",\
+             \"command\":\"tar cf {{target.tar}} {{file}}\"},\
+             {\"description\":\"Extract an archive:\",\"command\":\"tar xf {{source.tar}}\"}]}\n"
+        );
+    }
+}
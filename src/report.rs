@@ -0,0 +1,113 @@
+//! Reporting of errors, warnings and informational messages on stderr.
+
+use ansi_term::Style;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::error::Error;
+
+use tealdeer::TealdeerError;
+
+/// The format of messages on stderr.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorFormat {
+    /// Human-readable text
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+/// The severity of a message.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A message in the JSON error format.
+#[derive(Debug, Serialize)]
+struct JsonMessage<'a> {
+    level: Level,
+    kind: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
+}
+
+/// Prints messages to stderr in the selected format.
+#[derive(Debug, Copy, Clone)]
+pub struct Reporter {
+    format: ErrorFormat,
+}
+
+impl Reporter {
+    pub fn new(format: ErrorFormat) -> Self {
+        Self { format }
+    }
+
+    /// Return whether messages are printed as human-readable text.
+    pub fn is_text(self) -> bool {
+        self.format == ErrorFormat::Text
+    }
+
+    /// Report a message. The `kind` identifies the message in the JSON
+    /// format, the `details` are only included in the JSON format.
+    pub fn report(self, level: Level, kind: &str, message: &str, details: Option<Value>) {
+        self.report_styled(level, kind, message, details, Style::default());
+    }
+
+    /// Report a message, using the given style in the text format.
+    pub fn report_styled(
+        self,
+        level: Level,
+        kind: &str,
+        message: &str,
+        details: Option<Value>,
+        style: Style,
+    ) {
+        match self.format {
+            ErrorFormat::Text => eprintln!("{}", style.paint(message)),
+            ErrorFormat::Json => {
+                let json = JsonMessage {
+                    level,
+                    kind,
+                    message,
+                    details,
+                };
+                match serde_json::to_string(&json) {
+                    Ok(json) => eprintln!("{}", json),
+                    Err(e) => eprintln!("Could not serialize message: {}", e),
+                }
+            }
+        }
+    }
+
+    /// Report an error, prefixed with a description of what failed.
+    ///
+    /// The JSON details contain the exit code and the underlying error.
+    pub fn error(self, context: &str, e: &TealdeerError) {
+        self.error_with_details(context, e, serde_json::Map::new());
+    }
+
+    /// Report an error with additional JSON details.
+    pub fn error_with_details(
+        self,
+        context: &str,
+        e: &TealdeerError,
+        mut details: serde_json::Map<String, Value>,
+    ) {
+        let message = if context.is_empty() {
+            e.to_string()
+        } else {
+            format!("{}: {}", context, e)
+        };
+        details.insert("exit_code".into(), e.exit_code().into());
+        if let Some(source) = e.source() {
+            details.insert("source".into(), source.to_string().into());
+        }
+        self.report(Level::Error, e.kind(), &message, Some(details.into()));
+    }
+}
@@ -0,0 +1,427 @@
+//! A lightweight lexer to highlight shell syntax in example code.
+
+/// A token of an example code line.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ShellToken<'a> {
+    /// Whitespace and words without special meaning
+    Text(&'a str),
+    /// The name of the command the page is about
+    CommandName(&'a str),
+    /// The first plain word following the command name (e.g. `commit` in `git commit`)
+    Subcommand(&'a str),
+    /// A `-short` or `--long` flag, including an `=value` suffix
+    Flag(&'a str),
+    /// A 'single' or "double" quoted string, including the quotes
+    String(&'a str),
+    /// Pipes, redirections, command separators and substitutions
+    Operator(&'a str),
+    /// An environment variable reference like `$HOME` or `${HOME}`
+    EnvVar(&'a str),
+    /// A {{placeholder}}, without the curly braces
+    Variable(&'a str),
+}
+
+/// Characters that form operators.
+const OPERATOR_CHARS: &[char] = &['|', '&', ';', '<', '>', '(', ')', '`'];
+
+/// Characters that end a word.
+fn is_word_end(c: char) -> bool {
+    c.is_whitespace() || OPERATOR_CHARS.contains(&c) || c == '\'' || c == '"' || c == '$'
+}
+
+/// A lexer that splits example code into `ShellToken`s.
+struct Lexer<'a> {
+    code: &'a str,
+    command: &'a str,
+    pos: usize,
+    tokens: Vec<ShellToken<'a>>,
+    /// Whether the previous word was the command name.
+    after_command: bool,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self) -> Option<char> {
+        self.code[self.pos..].chars().next()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.code[self.pos..]
+    }
+
+    /// Return whether the current position is at the start of a word.
+    fn at_word_start(&self) -> bool {
+        self.code[..self.pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| c.is_whitespace() || OPERATOR_CHARS.contains(&c))
+    }
+
+    /// Push a token. Adjacent text tokens are merged.
+    fn push(&mut self, token: ShellToken<'a>) {
+        if let (Some(ShellToken::Text(previous)), ShellToken::Text(text)) =
+            (self.tokens.last(), token)
+        {
+            let start = self.offset(previous);
+            if start + previous.len() == self.offset(text) {
+                let merged = &self.code[start..start + previous.len() + text.len()];
+                *self.tokens.last_mut().unwrap() = ShellToken::Text(merged);
+                return;
+            }
+        }
+        self.tokens.push(token);
+    }
+
+    /// Return the byte offset of a slice of the code.
+    fn offset(&self, slice: &str) -> usize {
+        slice.as_ptr() as usize - self.code.as_ptr() as usize
+    }
+
+    /// Consume `len` bytes and return them.
+    fn take(&mut self, len: usize) -> &'a str {
+        let taken = &self.code[self.pos..self.pos + len];
+        self.pos += len;
+        taken
+    }
+
+    /// Consume a {{placeholder}}. Unclosed placeholders extend to the end of the line.
+    fn lex_variable(&mut self) {
+        self.pos += 2;
+        let len = self.rest().find("}}").unwrap_or_else(|| self.rest().len());
+        let variable = self.take(len);
+        self.push(ShellToken::Variable(variable));
+        if self.rest().starts_with("}}") {
+            self.pos += 2;
+        }
+    }
+
+    /// Consume an environment variable reference, if there is one.
+    fn lex_env_var(&mut self) -> bool {
+        let rest = &self.rest()[1..];
+        let len = if rest.starts_with('{') {
+            match rest.find('}') {
+                Some(end) => end + 1,
+                None => return false,
+            }
+        } else if rest.starts_with(&['?', '@', '#', '$'][..]) {
+            1
+        } else {
+            rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len())
+        };
+        if len == 0 {
+            return false;
+        }
+        let env_var = self.take(len + 1);
+        self.push(ShellToken::EnvVar(env_var));
+        true
+    }
+
+    /// Consume a quoted string, splitting out placeholders and (in double
+    /// quoted strings) environment variables.
+    fn lex_string(&mut self, quote: char) {
+        let mut start = self.pos;
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            if self.rest().starts_with("{{") || (quote == '"' && c == '$') {
+                let before = &self.code[start..self.pos];
+                if self.rest().starts_with("{{") {
+                    if !before.is_empty() {
+                        self.push(ShellToken::String(before));
+                    }
+                    self.lex_variable();
+                } else {
+                    let pos = self.pos;
+                    if !before.is_empty() {
+                        self.push(ShellToken::String(before));
+                    }
+                    if !self.lex_env_var() {
+                        self.pos = pos + 1;
+                        self.push(ShellToken::String(&self.code[pos..self.pos]));
+                    }
+                }
+                start = self.pos;
+                continue;
+            }
+            self.pos += c.len_utf8();
+            if c == '\\' && quote == '"' {
+                if let Some(escaped) = self.peek() {
+                    self.pos += escaped.len_utf8();
+                }
+            } else if c == quote {
+                break;
+            }
+        }
+        if start < self.pos {
+            self.push(ShellToken::String(&self.code[start..self.pos]));
+        }
+    }
+
+    /// Consume a run of operator characters, including a leading file
+    /// descriptor number of a redirection (e.g. `2>`).
+    fn lex_operator(&mut self) {
+        let len = self
+            .rest()
+            .find(|c: char| !(OPERATOR_CHARS.contains(&c) || c.is_ascii_digit()))
+            .unwrap_or_else(|| self.rest().len());
+        let operator = self.take(len);
+        self.push(ShellToken::Operator(operator));
+    }
+
+    /// Consume a word, i.e. a flag, the command name, a subcommand or plain text.
+    fn lex_word(&mut self) {
+        let word_start = self.at_word_start();
+        let rest = self.rest();
+
+        // Redirections with a file descriptor, e.g. `2>` or `2>&1`
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if word_start && digits > 0 && rest[digits..].starts_with(&['>', '<'][..]) {
+            self.lex_operator();
+            return;
+        }
+
+        // Only highlight command names that are a whole word, e.g. not `git`
+        // in `gitk`
+        let is_command = !self.command.is_empty()
+            && rest.starts_with(self.command)
+            && rest[self.command.len()..]
+                .chars()
+                .next()
+                .is_none_or(is_word_end);
+        if word_start && is_command {
+            let command = self.take(self.command.len());
+            self.push(ShellToken::CommandName(command));
+            self.after_command = true;
+            return;
+        }
+
+        let mut len = 0;
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if is_word_end(c) || rest[i..].starts_with("{{") {
+                break;
+            }
+            len = i + c.len_utf8();
+            if c == '\\' {
+                if let Some((j, escaped)) = chars.next() {
+                    len = j + escaped.len_utf8();
+                }
+            }
+        }
+        if len == 0 {
+            // A lone special character, e.g. a `$` without a variable name
+            len = rest.chars().next().map_or(0, char::len_utf8);
+        }
+        let word = self.take(len);
+
+        let is_flag = word_start
+            && word.len() > 1
+            && word.starts_with('-')
+            && !word[1..].starts_with(char::is_whitespace);
+        let is_subcommand = self.after_command
+            && word_start
+            && word.starts_with(|c: char| c.is_ascii_alphabetic())
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        self.after_command = false;
+
+        if is_flag {
+            self.push(ShellToken::Flag(word));
+        } else if is_subcommand && self.peek().is_none_or(char::is_whitespace) {
+            self.push(ShellToken::Subcommand(word));
+        } else {
+            self.push(ShellToken::Text(word));
+        }
+    }
+
+    fn lex(mut self) -> Vec<ShellToken<'a>> {
+        while let Some(c) = self.peek() {
+            if self.rest().starts_with("{{") {
+                self.after_command = false;
+                self.lex_variable();
+            } else if c.is_whitespace() {
+                let len = self
+                    .rest()
+                    .find(|c: char| !c.is_whitespace())
+                    .unwrap_or_else(|| self.rest().len());
+                let whitespace = self.take(len);
+                self.push(ShellToken::Text(whitespace));
+            } else if c == '\'' || c == '"' {
+                self.after_command = false;
+                self.lex_string(c);
+            } else if c == '$' && self.rest()[1..].starts_with('(') {
+                self.after_command = false;
+                let operator = self.take(2);
+                self.push(ShellToken::Operator(operator));
+            } else if c == '$' && self.lex_env_var() {
+                self.after_command = false;
+            } else if OPERATOR_CHARS.contains(&c) {
+                self.after_command = false;
+                self.lex_operator();
+            } else {
+                self.lex_word();
+            }
+        }
+        self.tokens
+    }
+}
+
+/// Split a line of example code into `ShellToken`s.
+///
+/// The `command` is the name of the page's command, which is highlighted at
+/// the start of words.
+pub fn lex<'a>(code: &'a str, command: &'a str) -> Vec<ShellToken<'a>> {
+    Lexer {
+        code,
+        command,
+        pos: 0,
+        tokens: Vec::new(),
+        after_command: false,
+    }
+    .lex()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lex, ShellToken::*};
+
+    #[test]
+    fn test_command_and_variables() {
+        assert_eq!(
+            lex("inkscape {{filename.svg}}", "inkscape"),
+            vec![CommandName("inkscape"), Text(" "), Variable("filename.svg")]
+        );
+        assert_eq!(
+            lex("tar {{unclosed", "tar"),
+            vec![CommandName("tar"), Text(" "), Variable("unclosed")]
+        );
+    }
+
+    #[test]
+    fn test_command_name_prefix() {
+        assert_eq!(lex("gitk --all", "git"), vec![Text("gitk "), Flag("--all")]);
+        assert_eq!(lex("git-lfs", "git"), vec![Text("git-lfs")]);
+        assert_eq!(
+            lex("git|git", "git"),
+            vec![CommandName("git"), Operator("|"), CommandName("git")]
+        );
+    }
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(
+            lex("ls -la --color=auto - {{path}}", "ls"),
+            vec![
+                CommandName("ls"),
+                Text(" "),
+                Flag("-la"),
+                Text(" "),
+                Flag("--color=auto"),
+                Text(" - "),
+                Variable("path")
+            ]
+        );
+        assert_eq!(
+            lex("x --export-pdf={{file.pdf}}", "x"),
+            vec![
+                CommandName("x"),
+                Text(" "),
+                Flag("--export-pdf="),
+                Variable("file.pdf")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subcommand() {
+        assert_eq!(
+            lex("git commit --amend", "git"),
+            vec![
+                CommandName("git"),
+                Text(" "),
+                Subcommand("commit"),
+                Text(" "),
+                Flag("--amend")
+            ]
+        );
+        assert_eq!(
+            lex("git {{path}} foo", "git"),
+            vec![
+                CommandName("git"),
+                Text(" "),
+                Variable("path"),
+                Text(" foo")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(
+            lex(r#"echo 'a $b' "x $HOME {{y}}\" z""#, "echo"),
+            vec![
+                CommandName("echo"),
+                Text(" "),
+                String("'a $b'"),
+                Text(" "),
+                String("\"x "),
+                EnvVar("$HOME"),
+                String(" "),
+                Variable("y"),
+                String("\\\" z\"")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_operators_and_env_vars() {
+        assert_eq!(
+            lex("cat $FILE ${DIR} 2>&1 | grep foo > out", "cat"),
+            vec![
+                CommandName("cat"),
+                Text(" "),
+                EnvVar("$FILE"),
+                Text(" "),
+                EnvVar("${DIR}"),
+                Text(" "),
+                Operator("2>&1"),
+                Text(" "),
+                Operator("|"),
+                Text(" grep foo "),
+                Operator(">"),
+                Text(" out")
+            ]
+        );
+        assert_eq!(
+            lex("echo $(date) && cd -", "echo"),
+            vec![
+                CommandName("echo"),
+                Text(" "),
+                Operator("$("),
+                Text("date"),
+                Operator(")"),
+                Text(" "),
+                Operator("&&"),
+                Text(" cd -")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_only_at_word_start() {
+        assert_eq!(
+            lex("inkscape --use-inkscape=v3.0 | inkscape", "inkscape"),
+            vec![
+                CommandName("inkscape"),
+                Text(" "),
+                Flag("--use-inkscape=v3.0"),
+                Text(" "),
+                Operator("|"),
+                Text(" "),
+                CommandName("inkscape")
+            ]
+        );
+    }
+}
@@ -0,0 +1,804 @@
+//! Page sources that pages can be looked up in.
+//!
+//! A `SourceChain` queries an ordered list of `PageSource`s, e.g. the
+//! custom pages directory before the extracted cache.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use tar::Archive;
+use walkdir::{DirEntry, WalkDir};
+
+use crate::cache::PageLookupResult;
+use crate::error::TealdeerError::{self, CacheError};
+use crate::types::PageInfo;
+
+/// The content of a page, either a file or a string held in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageContent {
+    File(PathBuf),
+    Memory { name: String, content: String },
+}
+
+impl PageContent {
+    /// Return a reader for the page content.
+    pub fn reader(&self) -> Result<Box<dyn BufRead + '_>, TealdeerError> {
+        match self {
+            Self::File(path) => File::open(path)
+                .map(|file| Box::new(BufReader::new(file)) as Box<dyn BufRead>)
+                .map_err(|e| CacheError("Could not open file".into(), Some(e.into()))),
+            Self::Memory { content, .. } => Ok(Box::new(content.as_bytes())),
+        }
+    }
+
+    /// Return the file name of the page (e.g. `tar.md`).
+    pub fn name(&self) -> Cow<'_, str> {
+        match self {
+            Self::File(path) => path.file_name().unwrap_or_default().to_string_lossy(),
+            Self::Memory { name, .. } => Cow::from(name.as_str()),
+        }
+    }
+}
+
+/// A page found by a `PageSource`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcePage {
+    pub content: PageContent,
+    pub info: PageInfo,
+}
+
+/// A backend that pages can be looked up in.
+pub trait PageSource {
+    /// Find a page for the given platform (falling back to `common`) in the
+    /// first of the given languages that has it.
+    fn find(&self, name: &str, platform: Option<&str>, languages: &[String]) -> Option<SourcePage>;
+
+    /// Find a patch that is appended to the page with the given name.
+    fn find_patch(&self, _name: &str) -> Option<PageContent> {
+        None
+    }
+
+    /// Return the names of the pages for the given platform and `common`.
+    fn list(&self, platform: Option<&str>) -> Result<Vec<String>, TealdeerError>;
+
+    /// Return the name, platform and language of every page in the source,
+    /// including all platforms. Only pages in the given language are
+    /// returned, or all translations if `language` is `None`.
+    ///
+    /// The default implementation only knows the pages returned by `list`
+    /// and looks them up in the given language (or English).
+    fn entries(&self, language: Option<&str>) -> Result<Vec<(String, PageInfo)>, TealdeerError> {
+        let languages = [language.unwrap_or("en").to_string()];
+        Ok(self
+            .list(None)?
+            .into_iter()
+            .filter_map(|name| {
+                let info = self.metadata(&name, None, &languages)?;
+                Some((name, info))
+            })
+            .collect())
+    }
+
+    /// Return the platform and language of a page without reading it.
+    fn metadata(
+        &self,
+        name: &str,
+        platform: Option<&str>,
+        languages: &[String],
+    ) -> Option<PageInfo> {
+        self.find(name, platform, languages).map(|page| page.info)
+    }
+}
+
+/// Return the directory of the pages in the given language.
+fn language_dir(language: &str) -> Cow<'_, str> {
+    if language == "en" {
+        Cow::from("pages")
+    } else {
+        Cow::from(format!("pages.{}", language))
+    }
+}
+
+/// Return the platforms to search, in order.
+fn platforms(platform: Option<&str>) -> impl Iterator<Item = &str> {
+    platform.into_iter().chain(std::iter::once("common"))
+}
+
+/// The pages of the extracted cache (`pages[.<lang>]/<platform>/<name>.md`).
+#[derive(Debug, Clone)]
+pub struct CacheSource {
+    dir: PathBuf,
+}
+
+impl CacheSource {
+    /// Create a source for the directory containing the `pages` directories.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Return the sorted languages that have a pages directory.
+    pub fn languages(&self) -> Result<Vec<String>, TealdeerError> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| CacheError("Could not read the cache directory".into(), Some(e.into())))?;
+        let mut languages: Vec<String> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                match name.as_str() {
+                    "pages" => Some("en".into()),
+                    _ => name.strip_prefix("pages.").map(String::from),
+                }
+            })
+            .collect();
+        languages.sort();
+        Ok(languages)
+    }
+}
+
+impl PageSource for CacheSource {
+    fn find(&self, name: &str, platform: Option<&str>, languages: &[String]) -> Option<SourcePage> {
+        let file_name = format!("{}.md", name);
+        for platform in platforms(platform) {
+            for language in languages {
+                let path = self
+                    .dir
+                    .join(&*language_dir(language))
+                    .join(platform)
+                    .join(&file_name);
+                if path.is_file() {
+                    return Some(SourcePage {
+                        content: PageContent::File(path),
+                        info: PageInfo {
+                            platform: Some(platform.into()),
+                            language: Some(language.clone()),
+                            custom: false,
+                        },
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn list(&self, platform: Option<&str>) -> Result<Vec<String>, TealdeerError> {
+        // Closure that allows the WalkDir instance to traverse platform
+        // specific and common page directories, but not others.
+        let should_walk = |entry: &DirEntry| -> bool {
+            let file_type = entry.file_type();
+            let file_name = match entry.file_name().to_str() {
+                Some(name) => name,
+                None => return false,
+            };
+            if file_type.is_dir() {
+                return platforms(platform).any(|platform| platform == file_name);
+            }
+            file_type.is_file()
+        };
+
+        // Recursively walk through common and (if applicable) platform specific directory
+        let mut pages = WalkDir::new(self.dir.join("pages"))
+            .min_depth(1) // Skip root directory
+            .into_iter()
+            .filter_entry(|e| should_walk(e)) // Filter out pages for other architectures
+            .filter_map(Result::ok) // Convert results to options, filter out errors
+            .filter_map(|e| {
+                let path = e.path();
+                let extension = path.extension().and_then(OsStr::to_str).unwrap_or("");
+                if e.file_type().is_file() && extension == "md" {
+                    path.file_stem().and_then(OsStr::to_str).map(String::from)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<String>>();
+        pages.sort();
+        pages.dedup();
+        Ok(pages)
+    }
+
+    fn entries(&self, language: Option<&str>) -> Result<Vec<(String, PageInfo)>, TealdeerError> {
+        let languages = match language {
+            Some(language) => vec![language.to_string()],
+            None => self.languages()?,
+        };
+        let mut entries = Vec::new();
+        for language in languages {
+            // Walk the `<platform>/<name>.md` files of the language
+            let files = WalkDir::new(self.dir.join(&*language_dir(&language)))
+                .min_depth(2)
+                .max_depth(2)
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file());
+            for file in files {
+                let path = file.path();
+                if path.extension() != Some(OsStr::new("md")) {
+                    continue;
+                }
+                let name = path.file_stem().and_then(OsStr::to_str);
+                let platform = path
+                    .parent()
+                    .and_then(Path::file_name)
+                    .and_then(OsStr::to_str);
+                if let (Some(name), Some(platform)) = (name, platform) {
+                    entries.push((
+                        name.to_string(),
+                        PageInfo {
+                            platform: Some(platform.into()),
+                            language: Some(language.clone()),
+                            custom: false,
+                        },
+                    ));
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// The custom pages directory, containing `<name>.page` files that replace
+/// pages and `<name>.patch` files that are appended to them.
+#[derive(Debug, Clone)]
+pub struct CustomPagesSource {
+    dir: PathBuf,
+}
+
+impl CustomPagesSource {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl PageSource for CustomPagesSource {
+    fn find(
+        &self,
+        name: &str,
+        _platform: Option<&str>,
+        _languages: &[String],
+    ) -> Option<SourcePage> {
+        let path = self.dir.join(format!("{}.page", name));
+        if path.is_file() {
+            Some(SourcePage {
+                content: PageContent::File(path),
+                info: PageInfo {
+                    custom: true,
+                    ..PageInfo::default()
+                },
+            })
+        } else {
+            None
+        }
+    }
+
+    fn find_patch(&self, name: &str) -> Option<PageContent> {
+        Some(self.dir.join(format!("{}.patch", name)))
+            .filter(|path| path.is_file())
+            .map(PageContent::File)
+    }
+
+    fn list(&self, _platform: Option<&str>) -> Result<Vec<String>, TealdeerError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(vec![]),
+        };
+        let mut pages = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some(OsStr::new("page")) && path.is_file())
+            .filter_map(|path| path.file_stem().and_then(OsStr::to_str).map(String::from))
+            .collect::<Vec<String>>();
+        pages.sort();
+        Ok(pages)
+    }
+
+    fn entries(&self, _language: Option<&str>) -> Result<Vec<(String, PageInfo)>, TealdeerError> {
+        let info = PageInfo {
+            custom: true,
+            ..PageInfo::default()
+        };
+        Ok(self
+            .list(None)?
+            .into_iter()
+            .map(|name| (name, info.clone()))
+            .collect())
+    }
+}
+
+/// Pages held in memory, e.g. for tests or pages embedded in another tool.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    /// Pages by language, platform and name
+    pages: BTreeMap<(String, String, String), String>,
+    /// Patches by name
+    patches: BTreeMap<String, String>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a page for a language and platform.
+    pub fn with_page<S: Into<String>>(
+        mut self,
+        language: &str,
+        platform: &str,
+        name: &str,
+        content: S,
+    ) -> Self {
+        self.pages.insert(
+            (language.into(), platform.into(), name.into()),
+            content.into(),
+        );
+        self
+    }
+
+    /// Add a patch that is appended to the page with the given name.
+    pub fn with_patch<S: Into<String>>(mut self, name: &str, content: S) -> Self {
+        self.patches.insert(name.into(), content.into());
+        self
+    }
+}
+
+impl PageSource for MemorySource {
+    fn find(&self, name: &str, platform: Option<&str>, languages: &[String]) -> Option<SourcePage> {
+        for platform in platforms(platform) {
+            for language in languages {
+                let key = (language.clone(), platform.to_string(), name.to_string());
+                if let Some(content) = self.pages.get(&key) {
+                    return Some(SourcePage {
+                        content: PageContent::Memory {
+                            name: format!("{}.md", name),
+                            content: content.clone(),
+                        },
+                        info: PageInfo {
+                            platform: Some(platform.into()),
+                            language: Some(language.clone()),
+                            custom: false,
+                        },
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn find_patch(&self, name: &str) -> Option<PageContent> {
+        self.patches.get(name).map(|content| PageContent::Memory {
+            name: format!("{}.patch", name),
+            content: content.clone(),
+        })
+    }
+
+    fn list(&self, platform: Option<&str>) -> Result<Vec<String>, TealdeerError> {
+        let mut pages = self
+            .pages
+            .keys()
+            .filter(|(language, page_platform, _)| {
+                language == "en" && platforms(platform).any(|platform| platform == page_platform)
+            })
+            .map(|(_, _, name)| name.clone())
+            .collect::<Vec<String>>();
+        pages.sort();
+        pages.dedup();
+        Ok(pages)
+    }
+
+    fn entries(&self, language: Option<&str>) -> Result<Vec<(String, PageInfo)>, TealdeerError> {
+        Ok(self
+            .pages
+            .keys()
+            .filter(|(page_language, _, _)| language.is_none_or(|l| l == page_language))
+            .map(|(language, platform, name)| {
+                let info = PageInfo {
+                    platform: Some(platform.clone()),
+                    language: Some(language.clone()),
+                    custom: false,
+                };
+                (name.clone(), info)
+            })
+            .collect())
+    }
+}
+
+/// A downloaded `.tar.gz` archive of the pages that has not been extracted.
+///
+/// The archive is read once when the source is created, and its pages are
+/// kept in memory.
+#[derive(Debug, Clone)]
+pub struct ArchiveSource {
+    pages: MemorySource,
+}
+
+impl ArchiveSource {
+    /// Read all pages of the archive at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, TealdeerError> {
+        Ok(Self {
+            pages: Self::read(path.as_ref())?,
+        })
+    }
+
+    /// Read all pages of the archive into a `MemorySource`.
+    fn read(path: &Path) -> Result<MemorySource, TealdeerError> {
+        let file = File::open(path)
+            .map_err(|e| CacheError("Could not open archive".into(), Some(e.into())))?;
+        let mut archive = Archive::new(GzDecoder::new(file));
+        let entries = archive
+            .entries()
+            .map_err(|e| CacheError("Could not read archive".into(), Some(e.into())))?;
+
+        let mut source = MemorySource::new();
+        for entry in entries {
+            let mut entry =
+                entry.map_err(|e| CacheError("Could not read archive".into(), Some(e.into())))?;
+            let path = match entry.path() {
+                Ok(path) => path.into_owned(),
+                Err(_) => continue,
+            };
+            // Paths have the form `<root>/pages[.<lang>]/<platform>/<name>.md`
+            let components: Vec<&str> = path
+                .components()
+                .filter_map(|component| match component {
+                    Component::Normal(part) => part.to_str(),
+                    _ => None,
+                })
+                .collect();
+            let (language_dir, platform, file_name) = match components[..] {
+                [_, language_dir, platform, file_name] => (language_dir, platform, file_name),
+                _ => continue,
+            };
+            let language = match language_dir {
+                "pages" => "en",
+                _ => match language_dir.strip_prefix("pages.") {
+                    Some(language) => language,
+                    None => continue,
+                },
+            };
+            let file_path = Path::new(file_name);
+            let name = match file_path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .filter(|_| file_path.extension() == Some(OsStr::new("md")))
+            {
+                Some(name) => name,
+                None => continue,
+            };
+            let mut content = String::new();
+            if entry.read_to_string(&mut content).is_ok() {
+                source = source.with_page(language, platform, name, content);
+            }
+        }
+        Ok(source)
+    }
+}
+
+impl PageSource for ArchiveSource {
+    fn find(&self, name: &str, platform: Option<&str>, languages: &[String]) -> Option<SourcePage> {
+        self.pages.find(name, platform, languages)
+    }
+
+    fn list(&self, platform: Option<&str>) -> Result<Vec<String>, TealdeerError> {
+        self.pages.list(platform)
+    }
+
+    fn entries(&self, language: Option<&str>) -> Result<Vec<(String, PageInfo)>, TealdeerError> {
+        self.pages.entries(language)
+    }
+}
+
+/// An ordered chain of page sources. The first source that has a page wins.
+#[derive(Default)]
+pub struct SourceChain {
+    sources: Vec<Box<dyn PageSource>>,
+}
+
+impl SourceChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a source to the chain.
+    pub fn with_source<S: PageSource + 'static>(mut self, source: S) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    /// Search for a page in all sources.
+    ///
+    /// Unless the page is a custom page, the first patch found in any source
+    /// is appended to it.
+    pub fn find_page(
+        &self,
+        name: &str,
+        platform: Option<&str>,
+        languages: &[String],
+    ) -> Option<PageLookupResult> {
+        let page = self
+            .sources
+            .iter()
+            .find_map(|source| source.find(name, platform, languages))?;
+        let patch = if page.info.custom {
+            None
+        } else {
+            self.sources
+                .iter()
+                .find_map(|source| source.find_patch(name))
+        };
+        Some(PageLookupResult::with_content(page.content, page.info).with_optional_patch(patch))
+    }
+
+    /// Return whether any source has a patch for the page with the given name.
+    pub fn has_patch(&self, name: &str) -> bool {
+        self.sources
+            .iter()
+            .any(|source| source.find_patch(name).is_some())
+    }
+
+    /// Return the name, platform and language of every page in all sources,
+    /// in the given language or in all languages (see `PageSource::entries`).
+    pub fn entries(
+        &self,
+        language: Option<&str>,
+    ) -> Result<Vec<(String, PageInfo)>, TealdeerError> {
+        let mut entries = Vec::new();
+        for source in &self.sources {
+            entries.extend(source.entries(language)?);
+        }
+        Ok(entries)
+    }
+
+    /// Return the sorted names of the pages in all sources.
+    pub fn list_pages(&self, platform: Option<&str>) -> Result<Vec<String>, TealdeerError> {
+        let mut pages = Vec::new();
+        for source in &self.sources {
+            pages.extend(source.list(platform)?);
+        }
+        pages.sort();
+        pages.dedup();
+        Ok(pages)
+    }
+}
+
+/// The number of single-character edits needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Suggest pages with a name similar to `name`, e.g. if it was misspelled
+/// or is the first part of a subcommand page. At most five pages are
+/// returned, the most similar first.
+pub fn suggest_pages(name: &str, pages: &[String]) -> Vec<String> {
+    let prefix = format!("{}-", name);
+    let mut suggestions: Vec<(usize, &String)> = pages
+        .iter()
+        .filter(|page| page.as_str() != name)
+        .filter_map(|page| {
+            let distance = edit_distance(name, page);
+            if distance <= 2 {
+                Some((distance, page))
+            } else if page.starts_with(&prefix) {
+                Some((3, page))
+            } else {
+                None
+            }
+        })
+        .collect();
+    suggestions.sort();
+    suggestions
+        .into_iter()
+        .take(5)
+        .map(|(_, page)| page.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        suggest_pages, ArchiveSource, CacheSource, MemorySource, PageContent, PageSource,
+        SourceChain, SourcePage,
+    };
+    use crate::error::TealdeerError;
+    use crate::types::PageInfo;
+    use flate2::{write::GzEncoder, Compression};
+
+    fn languages(languages: &[&str]) -> Vec<String> {
+        languages
+            .iter()
+            .map(|&language| String::from(language))
+            .collect()
+    }
+
+    fn chain() -> SourceChain {
+        SourceChain::new()
+            .with_source(MemorySource::new().with_page("en", "common", "custom", "# custom"))
+            .with_source(
+                MemorySource::new()
+                    .with_page("en", "common", "tar", "# tar")
+                    .with_page("de", "common", "tar", "# tar (de)")
+                    .with_page("en", "linux", "tar", "# tar (linux)")
+                    .with_page("en", "osx", "ls", "# ls")
+                    .with_patch("tar", "- Patched:"),
+            )
+    }
+
+    #[test]
+    fn test_memory_source_lookup_order() {
+        let source = MemorySource::new()
+            .with_page("en", "common", "tar", "# tar")
+            .with_page("de", "common", "tar", "# tar (de)")
+            .with_page("en", "linux", "tar", "# tar (linux)");
+
+        let info = source
+            .metadata("tar", Some("linux"), &languages(&["de", "en"]))
+            .unwrap();
+        assert_eq!(info.platform.as_deref(), Some("linux"));
+        assert_eq!(info.language.as_deref(), Some("en"));
+
+        let info = source
+            .metadata("tar", Some("osx"), &languages(&["de", "en"]))
+            .unwrap();
+        assert_eq!(info.platform.as_deref(), Some("common"));
+        assert_eq!(info.language.as_deref(), Some("de"));
+
+        assert_eq!(source.metadata("ls", None, &languages(&["en"])), None);
+    }
+
+    #[test]
+    fn test_chain_find_page_with_patch() {
+        let page = chain()
+            .find_page("tar", Some("linux"), &languages(&["en"]))
+            .unwrap();
+        let contents: Vec<&PageContent> = page.contents().collect();
+        assert_eq!(
+            contents,
+            vec![
+                &PageContent::Memory {
+                    name: "tar.md".into(),
+                    content: "# tar (linux)".into()
+                },
+                &PageContent::Memory {
+                    name: "tar.patch".into(),
+                    content: "- Patched:".into()
+                }
+            ]
+        );
+        assert_eq!(
+            page.page_info(),
+            &PageInfo {
+                platform: Some("linux".into()),
+                language: Some("en".into()),
+                custom: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_chain_list_pages() {
+        assert_eq!(
+            chain().list_pages(Some("linux")).unwrap(),
+            vec!["custom", "tar"]
+        );
+        assert_eq!(
+            chain().list_pages(Some("osx")).unwrap(),
+            vec!["custom", "ls", "tar"]
+        );
+    }
+
+    /// A source that only implements the required methods.
+    struct MinimalSource(MemorySource);
+
+    impl PageSource for MinimalSource {
+        fn find(
+            &self,
+            name: &str,
+            platform: Option<&str>,
+            languages: &[String],
+        ) -> Option<SourcePage> {
+            self.0.find(name, platform, languages)
+        }
+
+        fn list(&self, platform: Option<&str>) -> Result<Vec<String>, TealdeerError> {
+            self.0.list(platform)
+        }
+    }
+
+    #[test]
+    fn test_default_entries() {
+        let source = MinimalSource(
+            MemorySource::new()
+                .with_page("en", "common", "tar", "# tar")
+                .with_page("de", "common", "tar", "# tar (de)")
+                .with_page("en", "common", "ls", "# ls"),
+        );
+        let entries = source.entries(Some("de")).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, "tar");
+        assert_eq!(entries[0].1.language.as_deref(), Some("de"));
+        assert_eq!(source.entries(None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_cache_source_languages() {
+        let dir = tempfile::tempdir().unwrap();
+        for lang_dir in &["pages", "pages.de", "pages.pt_BR"] {
+            std::fs::create_dir(dir.path().join(lang_dir)).unwrap();
+        }
+        std::fs::write(dir.path().join("LICENSE.md"), "").unwrap();
+        assert_eq!(
+            CacheSource::new(dir.path()).languages().unwrap(),
+            vec!["de", "en", "pt_BR"]
+        );
+    }
+
+    #[test]
+    fn test_suggest_pages() {
+        let pages = languages(&["git", "git-commit", "gist", "tar", "gunzip"]);
+        assert_eq!(suggest_pages("gti", &pages), vec!["git"]);
+        assert_eq!(suggest_pages("git", &pages), vec!["gist", "git-commit"]);
+        assert!(suggest_pages("rsync", &pages).is_empty());
+    }
+
+    #[test]
+    fn test_archive_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tldr.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            Compression::default(),
+        ));
+        for (page_path, content) in &[
+            ("tldr-master/pages/common/tar.md", "# tar\n"),
+            ("tldr-master/pages.de/common/tar.md", "# tar (de)\n"),
+            ("tldr-master/pages/osx/ls.md", "# ls\n"),
+            ("tldr-master/README.md", "# tldr\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, page_path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let source = ArchiveSource::new(&path).unwrap();
+        let page = source
+            .find("tar", Some("linux"), &languages(&["de", "en"]))
+            .unwrap();
+        assert_eq!(page.info.language.as_deref(), Some("de"));
+        assert_eq!(
+            page.content,
+            PageContent::Memory {
+                name: "tar.md".into(),
+                content: "# tar (de)\n".into()
+            }
+        );
+        assert_eq!(source.list(Some("linux")).unwrap(), vec!["tar"]);
+        assert_eq!(source.list(Some("osx")).unwrap(), vec!["ls", "tar"]);
+        assert_eq!(source.entries(None).unwrap().len(), 3);
+
+        // The archive is only read when the source is created
+        std::fs::remove_file(&path).unwrap();
+        assert!(source
+            .find("ls", Some("osx"), &languages(&["en"]))
+            .is_some());
+        assert!(ArchiveSource::new(&path).is_err());
+    }
+}
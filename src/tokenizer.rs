@@ -2,8 +2,8 @@
 
 use std::io::BufRead;
 
+use crate::error::TealdeerError::{self, ParseError};
 use crate::types::LineType;
-use log::warn;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum TldrFormat {
@@ -11,18 +11,16 @@ pub enum TldrFormat {
     Undecided,
     /// The original format
     V1,
-    /// The new format (see https://github.com/tldr-pages/tldr/pull/958)
+    /// The new format (see <https://github.com/tldr-pages/tldr/pull/958>)
     V2,
 }
 
 /// A tokenizer is initialized with a `BufReader` instance that contains the
-/// entire Tldr page. It then returns tokens as `Option<LineType>`.
+/// entire Tldr page. It then returns tokens as `Result<Option<LineType>>`.
 #[derive(Debug)]
 pub struct Tokenizer<R: BufRead> {
     /// An instance of `R: BufRead`.
     reader: R,
-    /// Whether the first line has already been tokenized or not.
-    first_line: bool,
     /// Buffer for the current line. Used internally.
     current_line: String,
     /// The tldr page format.
@@ -36,67 +34,97 @@ where
     pub fn new(reader: R) -> Self {
         Self {
             reader,
-            first_line: true,
             current_line: String::new(),
             format: TldrFormat::Undecided,
         }
     }
 
-    pub fn next_token(&mut self) -> Option<LineType> {
+    /// Return the format of the page, which is known after the first token.
+    pub fn format(&self) -> &TldrFormat {
+        &self.format
+    }
+
+    /// Read the next line into `current_line`. Return `false` at the end of the page.
+    fn read_line(&mut self) -> Result<bool, TealdeerError> {
         self.current_line.clear();
-        let bytes_read = self.reader.read_line(&mut self.current_line);
-        match bytes_read {
-            Ok(0) => None,
-            Err(e) => {
-                warn!("Could not read line from token reader: {:?}", e);
-                None
+        self.reader
+            .read_line(&mut self.current_line)
+            .map(|bytes_read| bytes_read > 0)
+            .map_err(|e| ParseError("Could not read line".into(), Some(e.into())))
+    }
+
+    /// Determine the page format from the title and return the title token.
+    ///
+    /// A byte order mark and leading blank lines are skipped. Pages in the
+    /// original format start with a markdown line (e.g. `# title`), pages in
+    /// the new format start with a title underlined with `===`.
+    fn detect_format(&mut self) -> Result<Option<LineType>, TealdeerError> {
+        loop {
+            if !self.read_line()? {
+                return Ok(None);
             }
-            Ok(_) => {
-                // Handle new titles
-                if self.first_line && !self.current_line.starts_with('#') {
-                    // It's the new format! Drop next line.
-                    // (Hmm, is there a way to do this without an allocation?)
-                    let mut devnull = String::new();
-                    if let Err(e) = self.reader.read_line(&mut devnull) {
-                        warn!("Could not read line from token reader: {:?}", e);
-                        return None;
-                    }
-                    self.first_line = false;
-                    self.format = TldrFormat::V2;
-                    return Some(LineType::Title(self.current_line.trim_end().to_string()));
-                }
-
-                if self.first_line {
-                    // Clear `first_line` flag
-                    self.first_line = false;
-
-                    // It's the old format.
-                    self.format = TldrFormat::V1;
-                }
-
-                // Convert line to a `LineType` instance
-                match self.format {
-                    TldrFormat::V1 => Some(LineType::from_v1(&self.current_line[..])),
-                    TldrFormat::V2 => Some(LineType::from(&self.current_line[..])),
-                    TldrFormat::Undecided => panic!("Could not determine page format version"),
-                }
+            if !self
+                .current_line
+                .trim_start_matches('\u{feff}')
+                .trim()
+                .is_empty()
+            {
+                break;
             }
         }
+        let first_line = self
+            .current_line
+            .trim_start_matches('\u{feff}')
+            .trim()
+            .to_string();
+
+        if first_line.starts_with(&['#', '>', '-', '`'][..]) {
+            self.format = TldrFormat::V1;
+            return Ok(Some(LineType::from_v1(&first_line)));
+        }
+
+        self.read_line()?;
+        let underline = self.current_line.trim();
+        if !underline.is_empty() && underline.chars().all(|c| c == '=') {
+            self.format = TldrFormat::V2;
+            return Ok(Some(LineType::Title(first_line)));
+        }
+
+        Err(ParseError(
+            format!(
+                "Could not determine page format: Expected a `# title` line \
+                 or a title underlined with `===`, found \"{}\"",
+                first_line
+            ),
+            None,
+        ))
+    }
+
+    pub fn next_token(&mut self) -> Result<Option<LineType>, TealdeerError> {
+        match self.format {
+            TldrFormat::Undecided => self.detect_format(),
+            TldrFormat::V1 => Ok(self
+                .read_line()?
+                .then(|| LineType::from_v1(&self.current_line[..]))),
+            TldrFormat::V2 => Ok(self
+                .read_line()?
+                .then(|| LineType::from(&self.current_line[..]))),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Tokenizer;
+    use super::{TldrFormat, Tokenizer};
     use crate::types::LineType;
 
     #[test]
     fn test_first_line_old_format() {
         let input = "# The Title\n\n";
         let mut tokenizer = Tokenizer::new(input.as_bytes());
-        let title = tokenizer.next_token().unwrap();
+        let title = tokenizer.next_token().unwrap().unwrap();
         assert_eq!(title, LineType::Title("The Title".to_string()));
-        let empty = tokenizer.next_token().unwrap();
+        let empty = tokenizer.next_token().unwrap().unwrap();
         assert_eq!(empty, LineType::Empty);
     }
 
@@ -104,9 +132,36 @@ mod test {
     fn test_first_line_new_format() {
         let input = "The Title\n=========\n\n";
         let mut tokenizer = Tokenizer::new(input.as_bytes());
-        let title = tokenizer.next_token().unwrap();
+        let title = tokenizer.next_token().unwrap().unwrap();
         assert_eq!(title, LineType::Title("The Title".to_string()));
-        let empty = tokenizer.next_token().unwrap();
+        let empty = tokenizer.next_token().unwrap().unwrap();
         assert_eq!(empty, LineType::Empty);
     }
+
+    #[test]
+    fn test_bom_and_leading_blank_lines() {
+        let input = "\u{feff}\n  \n# The Title\n";
+        let mut tokenizer = Tokenizer::new(input.as_bytes());
+        let title = tokenizer.next_token().unwrap().unwrap();
+        assert_eq!(title, LineType::Title("The Title".to_string()));
+        assert_eq!(*tokenizer.format(), TldrFormat::V1);
+
+        let input = "\u{feff}\nThe Title\n===\n";
+        let mut tokenizer = Tokenizer::new(input.as_bytes());
+        let title = tokenizer.next_token().unwrap().unwrap();
+        assert_eq!(title, LineType::Title("The Title".to_string()));
+        assert_eq!(*tokenizer.format(), TldrFormat::V2);
+        assert_eq!(tokenizer.next_token().unwrap(), None);
+    }
+
+    #[test]
+    fn test_undecidable_format() {
+        let input = "The Title\n> Description\n";
+        let mut tokenizer = Tokenizer::new(input.as_bytes());
+        assert!(tokenizer.next_token().is_err());
+        assert_eq!(*tokenizer.format(), TldrFormat::Undecided);
+
+        let mut tokenizer = Tokenizer::new("\n\n".as_bytes());
+        assert_eq!(tokenizer.next_token().unwrap(), None);
+    }
 }
@@ -35,6 +35,46 @@ pub enum ColorOptions {
     Never,
 }
 
+/// The output format of pages.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Styled text for an ANSI terminal
+    Terminal,
+    /// Text without any styles
+    Plain,
+    /// The raw markdown of the page
+    Markdown,
+    /// A single line of JSON per page
+    Json,
+    /// Only the example commands
+    Commands,
+}
+
+/// A source of pages in the lookup chain.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    /// The `.page` and `.patch` files in the custom pages directory
+    Custom,
+    /// The pages extracted to the cache directory
+    Cache,
+}
+
+/// The number of colors supported by the terminal.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Deserialize)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    /// The 256 color xterm palette
+    #[serde(rename = "256")]
+    Ansi256,
+    /// The basic 16 color palette
+    #[serde(rename = "16")]
+    Ansi16,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum LineType {
     Empty,
@@ -91,9 +131,13 @@ impl LineType {
                     .trim_start_matches(|chr: char| chr == '-' || chr.is_whitespace())
                     .into(),
             ),
+            // Only the delimiting backticks are removed, the code may end in a backtick
             Some('`') if chars.last() == Some('`') => Self::ExampleCode(
                 trimmed
-                    .trim_matches(|chr: char| chr == '`' || chr.is_whitespace())
+                    .strip_prefix('`')
+                    .and_then(|code| code.strip_suffix('`'))
+                    .unwrap_or(trimmed)
+                    .trim()
                     .into(),
             ),
             Some(_) => Self::Other(trimmed.into()),
@@ -101,6 +145,17 @@ impl LineType {
     }
 }
 
+/// Information about a page that is shown in the page header.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PageInfo {
+    /// The platform directory the page was found in (e.g. `common`)
+    pub platform: Option<String>,
+    /// The language of the page (e.g. `en`)
+    pub language: Option<String>,
+    /// Whether the page is a custom page that replaces the page in the cache
+    pub custom: bool,
+}
+
 /// The reason why a certain path (e.g. config path or cache dir) was chosen.
 #[derive(Debug, PartialEq)]
 pub enum PathSource {
@@ -153,4 +208,16 @@ mod test {
             LineType::ExampleCode("$ cargo run".into())
         );
     }
+
+    #[test]
+    fn test_linetype_from_v1_backticks() {
+        assert_eq!(
+            LineType::from_v1("`echo $(date) `date``"),
+            LineType::ExampleCode("echo $(date) `date`".into())
+        );
+        assert_eq!(
+            LineType::from_v1("` `pwd` `"),
+            LineType::ExampleCode("`pwd`".into())
+        );
+    }
 }
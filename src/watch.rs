@@ -0,0 +1,231 @@
+//! Re-render local pages whenever they change on disk.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use ansi_term::{Color, Style};
+use atty::Stream;
+use serde_json::json;
+
+use tealdeer::internal::{lint_page, LintWarning, OutputFormat};
+use tealdeer::{Config, PageContent, PageLookupResult};
+
+use crate::print_page;
+use crate::report::{Level, Reporter};
+
+/// How often the files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The ANSI escape sequence to clear the screen and move the cursor home.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// A local page that is being watched.
+struct WatchedPage {
+    path: PathBuf,
+    /// The places where a patch for the page may appear, in order of preference
+    patch_paths: Vec<PathBuf>,
+}
+
+impl WatchedPage {
+    /// A patch is looked up next to the page and in the custom pages directory.
+    fn new(path: PathBuf, custom_pages_dir: Option<&Path>) -> Self {
+        let mut patch_paths = Vec::new();
+        if let Some(stem) = path.file_stem() {
+            let mut patch_name = OsString::from(stem);
+            patch_name.push(".patch");
+            patch_paths.push(path.with_file_name(&patch_name));
+            if let Some(dir) = custom_pages_dir {
+                patch_paths.push(dir.join(&patch_name));
+            }
+        }
+        Self { path, patch_paths }
+    }
+
+    /// Return the modification times of the page and all possible patches.
+    /// Files that don't exist have no modification time.
+    fn modification_times(&self) -> Vec<Option<SystemTime>> {
+        std::iter::once(&self.path)
+            .chain(&self.patch_paths)
+            .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+
+    fn patch_path(&self) -> Option<&PathBuf> {
+        self.patch_paths.iter().find(|path| path.is_file())
+    }
+
+    fn lookup(&self) -> PageLookupResult {
+        let patch = self
+            .patch_path()
+            .map(|path| PageContent::File(path.clone()));
+        PageLookupResult::with_page(self.path.clone()).with_optional_patch(patch)
+    }
+
+    /// Check the page and its patch for style guide violations, and return
+    /// the warnings together with the file they belong to.
+    ///
+    /// A patch is not a page on its own, so it is checked as the end of the
+    /// page it is appended to.
+    fn lint(&self) -> io::Result<Vec<(&Path, LintWarning)>> {
+        let mut content = fs::read_to_string(&self.path)?;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        let page_lines = content.lines().count();
+        let patch_path = self.patch_path();
+        if let Some(path) = patch_path {
+            content.push_str(&fs::read_to_string(path)?);
+        }
+        Ok(lint_page(&content)
+            .into_iter()
+            .map(|mut warning| match patch_path {
+                Some(path) if warning.line > page_lines => {
+                    warning.line -= page_lines;
+                    (path.as_path(), warning)
+                }
+                _ => (self.path.as_path(), warning),
+            })
+            .collect())
+    }
+}
+
+/// Render the pages, each followed by its style guide violations.
+fn render(
+    pages: &[WatchedPage],
+    format: OutputFormat,
+    config: &Config,
+    warning_style: Style,
+    reporter: Reporter,
+) {
+    // Don't write the escape sequence into pipes, files or machine-readable output
+    if format == OutputFormat::Terminal && atty::is(Stream::Stdout) {
+        print!("{}", CLEAR_SCREEN);
+    }
+    for page in pages {
+        let file = page.path.display().to_string();
+        if let Err(e) = print_page(&page.lookup(), format, config, &[]) {
+            reporter.error(&format!("Could not render {}", file), &e);
+            continue;
+        }
+        let warnings = match page.lint() {
+            Ok(warnings) => warnings,
+            Err(e) => {
+                reporter.report(
+                    Level::Error,
+                    "read_error",
+                    &format!("Could not read {}: {}", file, e),
+                    Some(json!({ "file": file })),
+                );
+                continue;
+            }
+        };
+        if !warnings.is_empty() {
+            println!();
+        }
+        for (path, warning) in warnings {
+            let message = format!("{}:{}", path.display(), warning);
+            println!("{}", warning_style.paint(message));
+        }
+    }
+    let _ = io::stdout().flush();
+}
+
+/// Render the local page files and render them again whenever they or their
+/// patches change. Changes are detected by polling, so that this works on
+/// every file system. This function only returns if a file name is invalid.
+pub fn watch_pages(
+    files: &[String],
+    format: OutputFormat,
+    config: &Config,
+    enable_styles: bool,
+    reporter: Reporter,
+) {
+    if files.iter().any(|file| file == "-") {
+        reporter.report(
+            Level::Error,
+            "usage",
+            "The --watch flag cannot be used to render stdin",
+            None,
+        );
+        return;
+    }
+
+    let warning_style = if enable_styles {
+        Style::new().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    let custom_pages_dir = config.directories.custom_pages_dir.as_deref();
+    let pages: Vec<WatchedPage> = files
+        .iter()
+        .map(|file| WatchedPage::new(PathBuf::from(file), custom_pages_dir))
+        .collect();
+
+    let mut last_times = None;
+    loop {
+        let times: Vec<Vec<Option<SystemTime>>> =
+            pages.iter().map(WatchedPage::modification_times).collect();
+        if last_times.as_ref() != Some(&times) {
+            render(&pages, format, config, warning_style, reporter);
+            last_times = Some(times);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::WatchedPage;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_patch_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        let custom_dir = tempfile::tempdir().unwrap();
+        let page = WatchedPage::new(dir.path().join("foo.md"), Some(custom_dir.path()));
+        assert_eq!(
+            page.patch_paths,
+            vec![
+                dir.path().join("foo.patch"),
+                custom_dir.path().join("foo.patch")
+            ]
+        );
+
+        fs::write(&page.path, "# foo\n").unwrap();
+        assert_eq!(page.lookup().contents().count(), 1);
+        assert_eq!(page.modification_times().iter().flatten().count(), 1);
+
+        fs::write(custom_dir.path().join("foo.patch"), "- Bar:\n").unwrap();
+        let lookup = page.lookup();
+        let names: Vec<String> = lookup.contents().map(|c| c.name().into()).collect();
+        assert_eq!(names, vec!["foo.md", "foo.patch"]);
+        assert_eq!(page.modification_times().iter().flatten().count(), 2);
+
+        let page = WatchedPage::new(Path::new("foo.md").into(), None);
+        assert_eq!(page.patch_paths, vec![Path::new("foo.patch")]);
+    }
+
+    #[test]
+    fn test_lint_with_patch() {
+        let dir = tempfile::tempdir().unwrap();
+        let page = WatchedPage::new(dir.path().join("foo.md"), None);
+        fs::write(
+            &page.path,
+            "# foo\n\n> Foo.\n> More information: <https://example.com>.\n\n- Run:\n\n`foo`",
+        )
+        .unwrap();
+        assert!(page.lint().unwrap().is_empty());
+
+        // Warnings in the patch refer to the lines of the patch
+        fs::write(&page.patch_paths[0], "- Bar\n\n`bar`\n").unwrap();
+        let warnings = page.lint().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].0, page.patch_paths[0]);
+        assert_eq!((warnings[0].1.line, warnings[0].1.column), (1, 5));
+    }
+}
@@ -50,11 +50,20 @@ impl TestEnv {
 
     /// Add entry for that environment to an OS-specific subfolder.
     fn add_os_entry(&self, os: &str, name: &str, contents: &str) {
+        self.add_lang_entry("en", os, name, contents);
+    }
+
+    /// Add entry for that environment to the pages of a language.
+    fn add_lang_entry(&self, lang: &str, os: &str, name: &str, contents: &str) {
+        let lang_dir = match lang {
+            "en" => "pages".to_string(),
+            _ => format!("pages.{}", lang),
+        };
         let dir = self
             .cache_dir
             .path()
             .join("tldr-master")
-            .join("pages")
+            .join(lang_dir)
             .join(os);
         create_dir_all(&dir).unwrap();
 
@@ -128,6 +137,55 @@ fn test_missing_cache() {
         .stderr(contains("Cache not found. Please run `tldr --update`."));
 }
 
+#[test]
+fn test_exit_codes() {
+    let testenv = TestEnv::new();
+
+    // Cache missing
+    testenv.command().args(&["sl"]).assert().code(3);
+
+    // Page not found
+    testenv.add_entry("foo", "# foo\n");
+    testenv
+        .command()
+        .args(&["sl"])
+        .assert()
+        .code(2)
+        .stderr(contains("Page sl not found in cache"));
+
+    // Config invalid
+    testenv.write_config("[display\n");
+    testenv
+        .command()
+        .args(&["foo"])
+        .assert()
+        .code(5)
+        .stderr(contains("Failed to parse config file: "));
+}
+
+#[test]
+fn test_json_error_format() {
+    let testenv = TestEnv::new();
+
+    testenv
+        .command()
+        .args(&["--error-format", "json", "sl"])
+        .assert()
+        .code(3)
+        .stderr(contains("{\"level\":\"error\",\"kind\":\"cache_missing\","));
+
+    testenv.add_entry("git", "# git\n");
+    testenv.add_entry("git-commit", "# git commit\n");
+    testenv
+        .command()
+        .args(&["--error-format", "json", "gti"])
+        .assert()
+        .code(2)
+        .stderr(contains("\"kind\":\"page_not_found\"").and(contains(
+            "\"details\":{\"exit_code\":2,\"page\":\"gti\",\"suggestions\":[\"git\"]}",
+        )));
+}
+
 #[test]
 fn test_update_cache() {
     let testenv = TestEnv::new();
@@ -271,7 +329,7 @@ fn test_show_paths() {
 fn test_os_specific_page() {
     let testenv = TestEnv::new();
 
-    testenv.add_os_entry("sunos", "truss", "contents");
+    testenv.add_os_entry("sunos", "truss", "# truss\n\n> Trace system calls.\n");
 
     testenv
         .command()
@@ -280,6 +338,20 @@ fn test_os_specific_page() {
         .success();
 }
 
+#[test]
+fn test_undecidable_page_format() {
+    let testenv = TestEnv::new();
+
+    testenv.add_entry("broken", "broken\n\n> Not a valid page.\n");
+
+    testenv
+        .command()
+        .args(&["broken"])
+        .assert()
+        .failure()
+        .stderr(contains("Could not determine page format"));
+}
+
 #[test]
 fn test_markdown_rendering() {
     let testenv = TestEnv::new();
@@ -295,6 +367,72 @@ fn test_markdown_rendering() {
         .stdout(similar(expected));
 }
 
+#[test]
+fn test_markdown_is_raw() {
+    let testenv = TestEnv::new();
+    testenv.write_config(format!(
+        "[directories]\ncustom_pages_dir = '{}'",
+        testenv.custom_pages_dir.path().to_str().unwrap()
+    ));
+
+    testenv.add_entry("inkscape-v2", include_str!("inkscape-v2.md"));
+    testenv.add_patch_entry("inkscape-v2", include_str!("inkscape-v2.patch"));
+
+    // The page and patch are printed byte for byte, even in the V2 format
+    testenv
+        .command()
+        .args(&["-m", "inkscape-v2"])
+        .assert()
+        .success()
+        .stdout(similar(format!(
+            "{}{}",
+            include_str!("inkscape-v2.md"),
+            include_str!("inkscape-v2.patch")
+        )));
+}
+
+#[test]
+fn test_output_formats() {
+    let testenv = TestEnv::new();
+
+    testenv.add_entry("which", include_str!("which-markdown.expected"));
+
+    testenv
+        .command()
+        .args(&["--format", "markdown", "which"])
+        .assert()
+        .success()
+        .stdout(similar(include_str!("which-markdown.expected")));
+
+    testenv
+        .command()
+        .args(&["--format", "commands", "which"])
+        .assert()
+        .success()
+        .stdout(similar("which {{executable}}\nwhich -a {{executable}}\n"));
+
+    testenv
+        .command()
+        .args(&["--format", "json", "which"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "{\"name\":\"which\",\"platform\":\"common\",\"language\":\"en\"",
+        ))
+        .stdout(contains(
+            "{\"description\":\"If there are multiple executables which match, display all:\",\
+             \"command\":\"which -a {{executable}}\"}",
+        ));
+
+    testenv
+        .command()
+        .args(&["--format", "plain", "--color", "always", "which"])
+        .assert()
+        .success()
+        .stdout(contains("      which -a executable\n"))
+        .stdout(contains("\x1b[").not());
+}
+
 fn _test_correct_rendering(
     input_file: &str,
     filename: &str,
@@ -317,6 +455,157 @@ fn _test_correct_rendering(
         .stdout(similar(expected));
 }
 
+#[test]
+fn test_render_stdin_and_multiple_files() {
+    let testenv = TestEnv::new();
+
+    let file_path = testenv.input_dir.path().join("foo.md");
+    let mut file = File::create(&file_path).unwrap();
+    file.write_all(b"# foo\n\n> Foo.\n\n- Run foo:\n\n`foo`\n")
+        .unwrap();
+    let missing_path = testenv.input_dir.path().join("missing.md");
+
+    assert_cmd::Command::from_std(testenv.command())
+        .args(&["--format", "commands", "-f", "-"])
+        .args(&[&file_path, &missing_path])
+        .write_stdin("# bar\n\n> Bar.\n\n- Run bar:\n\n`bar`\n")
+        .assert()
+        .failure()
+        .stdout(similar("bar\nfoo\n"))
+        .stderr(contains("Could not render "));
+}
+
+#[test]
+fn test_lint_pages() {
+    let testenv = TestEnv::new();
+
+    let valid_path = testenv.input_dir.path().join("valid.md");
+    let mut file = File::create(&valid_path).unwrap();
+    file.write_all(
+        "# valid\n\n> A valid page.\n> More information: <https://example.com>.\n\n\
+         - Run it:\n\n`valid {{file}}`\n"
+            .as_bytes(),
+    )
+    .unwrap();
+
+    let invalid_path = testenv.input_dir.path().join("inkscape-v1.md");
+    let mut file = File::create(&invalid_path).unwrap();
+    file.write_all(include_str!("inkscape-v1.md").as_bytes())
+        .unwrap();
+
+    testenv
+        .command()
+        .args(&["--lint", &valid_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(is_empty());
+
+    testenv
+        .command()
+        .args(&[
+            "--lint",
+            &valid_path.to_str().unwrap(),
+            &invalid_path.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stdout(similar(format!(
+            "{}:1:1: The description should contain a `More information: <url>.` line \
+             (missing-more-information)\n",
+            invalid_path.to_str().unwrap()
+        )));
+
+    // Without files there is nothing to check, which must not pass silently
+    for flag in &["--lint", "--fmt", "--convert", "--render"] {
+        testenv
+            .command()
+            .args(&[flag])
+            .assert()
+            .code(1)
+            .stdout(is_empty())
+            .stderr(contains("Missing <file> arguments"));
+    }
+}
+
+#[test]
+fn test_fmt_pages() {
+    let testenv = TestEnv::new();
+
+    let path = testenv.input_dir.path().join("inkscape-v2.md");
+    let mut file = File::create(&path).unwrap();
+    file.write_all(include_str!("inkscape-v2.md").as_bytes())
+        .unwrap();
+    let path = path.to_str().unwrap();
+
+    testenv
+        .command()
+        .args(&["--fmt", "--check", path])
+        .assert()
+        .failure()
+        .stdout(similar(format!("{}\n", path)));
+
+    // Checking can't be combined with overwriting the pages
+    testenv
+        .command()
+        .args(&["--fmt", "--check", "--in-place", path])
+        .assert()
+        .code(1)
+        .stdout(is_empty())
+        .stderr(contains("--check and --in-place can't be used together"));
+
+    testenv
+        .command()
+        .args(&["--fmt", "--in-place", path])
+        .assert()
+        .success()
+        .stdout(is_empty());
+
+    testenv
+        .command()
+        .args(&["--fmt", "--check", path])
+        .assert()
+        .success()
+        .stdout(is_empty());
+
+    testenv
+        .command()
+        .args(&["--fmt", path])
+        .assert()
+        .success()
+        .stdout(similar(std::fs::read_to_string(path).unwrap()));
+}
+
+#[test]
+fn test_convert_pages() {
+    let testenv = TestEnv::new();
+
+    let v1_path = testenv.input_dir.path().join("inkscape-v1.md");
+    let mut file = File::create(&v1_path).unwrap();
+    file.write_all(include_str!("inkscape-v1.md").as_bytes())
+        .unwrap();
+    let v1_path = v1_path.to_str().unwrap();
+
+    let v2_path = testenv.input_dir.path().join("inkscape-v2.md");
+    let mut file = File::create(&v2_path).unwrap();
+    file.write_all(include_str!("inkscape-v2.md").as_bytes())
+        .unwrap();
+    let v2_path = v2_path.to_str().unwrap();
+
+    testenv
+        .command()
+        .args(&["--convert", v2_path])
+        .assert()
+        .success()
+        .stdout(similar(include_str!("inkscape-v1.md")));
+
+    testenv
+        .command()
+        .args(&["--convert", v1_path])
+        .assert()
+        .failure()
+        .stderr(contains("not in the legacy format"));
+}
+
 /// An end-to-end integration test for direct file rendering (v1 syntax).
 #[test]
 fn test_correct_rendering_v1() {
@@ -362,6 +651,58 @@ fn test_rendering_color_never() {
     );
 }
 
+/// An end-to-end integration test for inline markdown in descriptions and example text.
+#[test]
+fn test_inline_markdown_rendering() {
+    let testenv = TestEnv::new();
+
+    let file_path = testenv.input_dir.path().join("inline.md");
+    let mut file = File::create(&file_path).unwrap();
+    file.write_all(
+        "# inline\n\n\
+         > Use `inline` *carefully*.\n\
+         > More information: <https://example.com>.\n\n\
+         - Run **without** `--flag`:\n\n\
+         `inline`\n"
+            .as_bytes(),
+    )
+    .unwrap();
+
+    testenv
+        .command()
+        .args(&["--color", "never", "-f", &file_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(similar(
+            "\n  Use inline carefully.\n  More information: https://example.com.\n\n  \
+             Run without --flag:\n\n      inline\n\n",
+        ));
+}
+
+/// An end-to-end integration test for shell syntax highlighting in example code.
+#[test]
+fn test_shell_highlighting_with_config() {
+    let testenv = TestEnv::new();
+    testenv.write_config(
+        "[style.example_code]\nforeground = \"cyan\"\n\
+         [style.example_flag]\nforeground = \"red\"\n\
+         [style.example_operator]\nbold = true\n",
+    );
+
+    let file_path = testenv.input_dir.path().join("shell.md");
+    let mut file = File::create(&file_path).unwrap();
+    file.write_all("# shell\n\n- Example:\n\n`shell --flag | less`\n".as_bytes())
+        .unwrap();
+
+    testenv
+        .command()
+        .args(&["--color", "always", "-f", &file_path.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(contains("\u{1b}[31m--flag"))
+        .stdout(contains("\u{1b}[1m|"));
+}
+
 /// An end-to-end integration test for rendering with custom syntax config.
 #[test]
 fn test_correct_rendering_with_config() {
@@ -396,6 +737,75 @@ fn test_correct_rendering_with_config() {
         .stdout(similar(expected));
 }
 
+/// Helper to render a small page with the specified arguments and return the assert.
+fn _render_themed(testenv: &TestEnv, args: &[&str]) -> assert_cmd::assert::Assert {
+    let file_path = testenv.input_dir.path().join("themed.md");
+    let mut file = File::create(&file_path).unwrap();
+    file.write_all("# themed\n\n- Example:\n\n`themed --flag`\n".as_bytes())
+        .unwrap();
+
+    testenv
+        .command()
+        .args(&["--color", "always", "-f", &file_path.to_str().unwrap()])
+        .args(args)
+        .assert()
+}
+
+#[test]
+fn test_builtin_theme() {
+    let testenv = TestEnv::new();
+
+    _render_themed(&testenv, &["--theme", "monochrome-bold"])
+        .success()
+        .stdout(contains("\u{1b}[1mExample:"))
+        .stdout(contains("\u{1b}[32m").not());
+
+    _render_themed(&testenv, &["--theme", "nonexistent"])
+        .failure()
+        .stderr(contains("Unknown theme: nonexistent"));
+}
+
+#[test]
+fn test_theme_file_with_overrides() {
+    let testenv = TestEnv::new();
+
+    let themes_dir = testenv.config_dir.path().join("themes");
+    create_dir_all(&themes_dir).unwrap();
+    let mut theme_file = File::create(themes_dir.join("mine.toml")).unwrap();
+    theme_file
+        .write_all(
+            "[example_text]\nforeground = \"red\"\n[example_flag]\nforeground = \"blue\"\n"
+                .as_bytes(),
+        )
+        .unwrap();
+    testenv
+        .write_config("[style]\ntheme = \"mine\"\n[style.example_text]\nforeground = \"yellow\"\n");
+
+    _render_themed(&testenv, &[])
+        .success()
+        .stdout(contains("\u{1b}[33mExample:"))
+        .stdout(contains("\u{1b}[34m--flag"));
+}
+
+#[test]
+fn test_color_depth_downsampling() {
+    let testenv = TestEnv::new();
+    testenv
+        .write_config("[style.example_text]\nforeground = { rgb = { r = 250, g = 20, b = 10 } }\n");
+
+    _render_themed(&testenv, &["--color-depth", "truecolor"])
+        .success()
+        .stdout(contains("\u{1b}[38;2;250;20;10mExample:"));
+
+    _render_themed(&testenv, &["--color-depth", "256"])
+        .success()
+        .stdout(contains("\u{1b}[38;5;196mExample:"));
+
+    _render_themed(&testenv, &["--color-depth", "16"])
+        .success()
+        .stdout(contains("\u{1b}[31mExample:"));
+}
+
 #[test]
 fn test_spaces_find_command() {
     let testenv = TestEnv::new();
@@ -464,6 +874,131 @@ fn test_list_flag_rendering() {
         .stdout("bar\nbaz\nfoo\nqux\n");
 }
 
+#[test]
+fn test_list_platform_and_language() {
+    let testenv = TestEnv::new();
+    testenv.add_entry("tar", "# tar\n");
+    testenv.add_os_entry("linux", "apt", "# apt\n");
+    testenv.add_os_entry("osx", "brew", "# brew\n");
+    testenv.add_lang_entry("de", "common", "tar", "");
+
+    testenv
+        .command()
+        .args(&["--list", "--os", "linux"])
+        .assert()
+        .success()
+        .stdout("apt\ntar\n");
+    testenv
+        .command()
+        .args(&["--list", "--os", "linux", "--platform", "osx"])
+        .assert()
+        .success()
+        .stdout("brew\n");
+    testenv
+        .command()
+        .args(&["--list", "--platform", "all"])
+        .assert()
+        .success()
+        .stdout("apt\nbrew\ntar\n");
+    testenv
+        .command()
+        .args(&["--list", "--platform", "all", "--language", "de"])
+        .assert()
+        .success()
+        .stdout("tar\n");
+    testenv
+        .command()
+        .args(&["--list", "--platform", "beos"])
+        .assert()
+        .failure()
+        .stderr(contains("Unknown platform: beos"));
+}
+
+#[test]
+fn test_coverage() {
+    let testenv = TestEnv::new();
+    testenv.add_entry("tar", "# tar\n\n- Create:\n\n`tar cf {{file}}`\n");
+    testenv.add_entry("ls", "# ls\n\n- List:\n\n`ls -l`\n");
+    testenv.add_lang_entry(
+        "de",
+        "common",
+        "tar",
+        "# tar\n\n- Erstellen:\n\n`tar cf {{datei}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(&["--coverage", "--long"])
+        .assert()
+        .success()
+        .stdout(similar(
+            "Language  Platform  Translated  Total  Coverage\n\
+             de        common             1      2     50.0%\n\
+             de        all                1      2     50.0%\n\
+             \n\
+             Missing (de):\n  common/ls\n\
+             \n\
+             Outdated (de):\n\
+             \n\
+             Errors (de):\n",
+        ));
+
+    testenv
+        .command()
+        .args(&["--coverage", "--language", "de", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "\"language\":\"de\",\"platforms\":[{\"platform\":\"common\",\
+             \"translated\":1,\"total\":2,\"percentage\":50.0}]",
+        ));
+}
+
+#[test]
+fn test_list_long() {
+    let testenv = TestEnv::new();
+    testenv.write_config(format!(
+        "[directories]\ncustom_pages_dir = '{}'",
+        testenv.custom_pages_dir.path().to_str().unwrap()
+    ));
+
+    testenv.add_entry("foo", "# foo\n\n> Foo things.\n> More information.\n");
+    testenv.add_os_entry("linux", "foo", "# foo\n\n> Foo things on Linux.\n");
+    testenv.add_entry("bar", "# bar\n\n> Bar things.\n");
+    testenv.add_patch_entry("bar", "- Bar more:\n\n`bar more`\n");
+    testenv.add_page_entry("baz", "# baz\n\n> My own baz.\n");
+
+    testenv
+        .command()
+        .args(&["--list", "--long", "--os", "osx"])
+        .assert()
+        .success()
+        .stdout(similar(
+            "bar  common        en  (patched) Bar things.\n\
+             baz                    (custom) My own baz.\n\
+             foo  common,linux  en  Foo things.\n",
+        ));
+
+    // The plain list contains the same pages
+    testenv
+        .command()
+        .args(&["--list", "--os", "osx"])
+        .assert()
+        .success()
+        .stdout(similar("bar\nbaz\nfoo\n"));
+
+    testenv
+        .command()
+        .args(&["--list", "--long", "--os", "linux", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "{\"name\":\"foo\",\"description\":\"Foo things on Linux.\",\
+             \"platforms\":[\"common\",\"linux\"],\"languages\":[\"en\"],\
+             \"custom\":false,\"patched\":false}\n",
+        ));
+}
+
 #[test]
 fn test_autoupdate_cache() {
     let testenv = TestEnv::new();
@@ -547,6 +1082,52 @@ fn test_custom_page_overwrites() {
         .stdout(similar(expected));
 }
 
+#[test]
+fn test_source_order() {
+    let testenv = TestEnv::new();
+    testenv.add_entry("foo", "# foo\n\n> Foo from the cache.\n");
+    testenv.add_page_entry("foo", "# foo\n\n> My own foo.\n");
+    let write_config = |sources: &str| {
+        testenv.write_config(format!(
+            "[directories]\ncustom_pages_dir = '{}'\nsources = {}",
+            testenv.custom_pages_dir.path().to_str().unwrap(),
+            sources
+        ))
+    };
+
+    write_config("['cache', 'custom']");
+    testenv
+        .command()
+        .args(&["foo"])
+        .assert()
+        .success()
+        .stdout(contains("Foo from the cache."));
+
+    write_config("['custom']");
+    testenv
+        .command()
+        .args(&["--list"])
+        .assert()
+        .success()
+        .stdout(similar("foo\n"));
+    testenv
+        .command()
+        .args(&["foo"])
+        .assert()
+        .success()
+        .stdout(contains("My own foo."));
+
+    write_config("[]");
+    testenv
+        .command()
+        .args(&["foo"])
+        .assert()
+        .code(5)
+        .stderr(contains(
+            "The list of page sources (directories.sources) is empty",
+        ));
+}
+
 /// End-End test to ensure that .patch files are appened to pages in the cache_dir
 #[test]
 fn test_custom_patch_appends_to_common() {
@@ -574,6 +1155,34 @@ fn test_custom_patch_appends_to_common() {
         .stdout(similar(expected));
 }
 
+/// End-End test for the page header and the separator before custom patches
+#[test]
+fn test_header_and_patch_separator() {
+    let testenv = TestEnv::new();
+
+    testenv.write_config(format!(
+        "[display]\nshow_title = true\nshow_patch_separator = true\n\
+         [directories]\ncustom_pages_dir = '{}'",
+        testenv.custom_pages_dir.path().to_str().unwrap()
+    ));
+
+    testenv.add_os_entry("linux", "inkscape-v2", include_str!("inkscape-v2.md"));
+    testenv.add_patch_entry("inkscape-v2", include_str!("inkscape-v2.patch"));
+
+    testenv
+        .command()
+        .args(&["inkscape-v2", "--os", "linux", "--color", "never"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "\n  inkscape (linux, en)\n\n  An SVG (Scalable Vector Graphics) editing program.\n",
+        ))
+        .stdout(contains(
+            "\n  --- inkscape-v2.patch ---\n  Custom inkscape entry\n",
+        ))
+        .stdout(contains("This header shouldn't be required").not());
+}
+
 /// End-End test to ensure that .patch files are not appended to .page files in the custom_pages_dir
 /// Maybe this interaction should change but I put this test here for the coverage
 #[test]
@@ -631,3 +1240,254 @@ fn test_pager_warning() {
         .success()
         .stderr(contains("pager flag not available on Windows"));
 }
+
+#[test]
+fn test_translations() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "tar",
+        "# tar\n\n> Archiver.\n\n- Create:\n\n`tar cf {{file}}`\n\n- List:\n\n`tar tf {{file}}`\n",
+    );
+    testenv.add_lang_entry(
+        "de",
+        "common",
+        "tar",
+        "# tar\n\n> Archivierer.\n\n- Erstellen:\n\n`tar cf {{datei}}`\n",
+    );
+
+    testenv
+        .command()
+        .args(&["--translations", "--language", "de,fr,en", "tar"])
+        .assert()
+        .success()
+        .stdout(similar(
+            "\n  tar (common, de)\n\n  Archivierer.\n\n  Erstellen:\n\n      tar cf datei\n\n\
+             \n  tar (common, en)\n\n  Archiver.\n\n  Create:\n\n      tar cf file\n\n  \
+             List:\n\n      tar tf file\n\n",
+        ));
+
+    testenv
+        .command()
+        .args(&["--paired", "--language", "de,en", "tar"])
+        .assert()
+        .success()
+        .stdout(similar(
+            "\n  tar (common, de, en)\n\n  de  Archivierer.\n  en  Archiver.\n\
+             \n  de  Erstellen:\n  en  Create:\n\
+             \n  de      tar cf datei\n  en      tar cf file\n\
+             \n  en  List:\n\n      tar tf file\n\n",
+        ));
+
+    testenv
+        .command()
+        .args(&["-t", "--language", "fr", "tar"])
+        .assert()
+        .failure()
+        .stderr(contains("Page tar not found in cache"));
+
+    // Without a language list, all translations in the cache are shown
+    testenv
+        .command()
+        .args(&["-t", "--format", "commands", "tar"])
+        .assert()
+        .success()
+        .stdout(contains("tar cf {{datei}}").and(contains("tar tf {{file}}")));
+}
+
+#[test]
+fn test_locale_precedence() {
+    let testenv = TestEnv::new();
+    testenv.add_entry("tar", "# tar\n\n> Archiver.\n");
+    testenv.add_lang_entry("de", "common", "tar", "# tar\n\n> Archivierer.\n");
+    testenv.add_lang_entry("pt_BR", "common", "tar", "# tar\n\n> Arquivador.\n");
+
+    let render = |vars: &[(&str, &str)]| {
+        let mut command = testenv.command();
+        for name in &["LC_ALL", "LC_MESSAGES", "LANG", "LANGUAGE"] {
+            command.env_remove(name);
+        }
+        command
+            .envs(vars.iter().copied())
+            .arg("tar")
+            .assert()
+            .success()
+    };
+    render(&[("LANG", "de_DE.UTF-8")]).stdout(contains("Archivierer."));
+    render(&[("LC_ALL", "pt-BR"), ("LANG", "de_DE.UTF-8")]).stdout(contains("Arquivador."));
+    render(&[("LC_MESSAGES", "de_AT@euro"), ("LANG", "C")]).stdout(contains("Archivierer."));
+    render(&[("LC_ALL", ""), ("LANG", "C.UTF-8")]).stdout(contains("Archiver."));
+    render(&[("LANGUAGE", "de")]).stdout(contains("Archiver."));
+}
+
+#[test]
+fn test_language_preferences() {
+    let testenv = TestEnv::new();
+    testenv.add_entry("tar", "# tar\n\n> Archiver.\n");
+    testenv.add_entry("ls", "# ls\n\n> Lister.\n");
+    testenv.add_lang_entry("de", "common", "tar", "# tar\n\n> Archivierer.\n");
+
+    // A comma separated list is tried in order
+    let show = |args: &[&str]| testenv.command().args(args).assert().success();
+    show(&["--language", "fr,de,en", "tar"]).stdout(contains("Archivierer."));
+    show(&["--language", "fr,de,en", "ls"]).stdout(contains("Lister."));
+
+    // The config applies unless the flag is given
+    testenv.write_config("[display]\nlanguages = ['de', 'en']");
+    show(&["tar"]).stdout(contains("Archivierer."));
+    show(&["ls"]).stdout(contains("Lister."));
+    show(&["--language", "en", "tar"]).stdout(contains("Archiver."));
+}
+
+#[test]
+fn test_alias_pages() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "egrep",
+        "# egrep\n\n> This command is an alias of `grep -E`.\n\n\
+         - View documentation for the original command:\n\n`tldr grep`\n",
+    );
+    testenv.add_entry("grep", "# grep\n\n> Matches patterns.\n");
+    testenv.add_entry(
+        "foo",
+        "# foo\n\n> This command is an alias of `bar`.\n\n- Bar:\n\n`tldr bar`\n",
+    );
+    testenv.add_entry(
+        "bar",
+        "# bar\n\n> This command is an alias of `foo`.\n\n- Foo:\n\n`tldr foo`\n",
+    );
+    testenv.add_entry(
+        "ls",
+        "# ls\n\n> `ll` is a common alias of `ls -l`.\n\n- List:\n\n`ls`\n\n- Long:\n\n`ls -l`\n",
+    );
+    testenv.add_entry(
+        "curl",
+        "# curl\n\n> Transfers data.\n\n- Download:\n\n`curl {{url}}`\n",
+    );
+    testenv.add_os_entry(
+        "windows",
+        "curl",
+        "# curl\n\n> In PowerShell, this command may be an alias of `Invoke-WebRequest`.\n\n\
+         - View documentation for the original command:\n\n`tldr curl -p common`\n",
+    );
+
+    testenv
+        .command()
+        .args(&["egrep"])
+        .assert()
+        .success()
+        .stdout(contains("Matches patterns.").and(contains("alias").not()))
+        .stderr(similar("`egrep` is an alias of `grep`.\n"));
+    testenv
+        .command()
+        .args(&["--quiet", "egrep"])
+        .assert()
+        .success()
+        .stderr(is_empty());
+    testenv
+        .command()
+        .args(&["foo"])
+        .assert()
+        .success()
+        .stdout(contains("This command is an alias of bar."))
+        .stderr(similar("The aliases form a cycle: foo -> bar -> foo\n"));
+
+    // Pages that only mention an alias are not alias pages
+    testenv
+        .command()
+        .args(&["ls"])
+        .assert()
+        .success()
+        .stdout(contains("is a common alias of ls -l."))
+        .stderr(is_empty());
+
+    // A platform page may refer to the common page of the same name
+    testenv
+        .command()
+        .args(&["--os", "windows", "curl"])
+        .assert()
+        .success()
+        .stdout(contains("Transfers data."))
+        .stderr(similar("`curl` is an alias of `curl`.\n"));
+
+    testenv.write_config("[display]\naliases = 'both'");
+    testenv
+        .command()
+        .args(&["egrep"])
+        .assert()
+        .success()
+        .stdout(contains("alias of grep -E").and(contains("Matches patterns.")))
+        .stderr(is_empty());
+
+    testenv.write_config("[display]\naliases = 'stub'");
+    testenv
+        .command()
+        .args(&["egrep"])
+        .assert()
+        .success()
+        .stdout(contains("alias of grep -E").and(contains("Matches patterns.").not()));
+
+    testenv
+        .command()
+        .args(&["--list", "--long"])
+        .assert()
+        .success()
+        .stdout(
+            contains("egrep  common          en  (alias of grep) ").and(contains(
+                "ls     common          en  `ll` is a common alias",
+            )),
+        );
+}
+
+#[test]
+fn test_subcommand_fallback() {
+    let testenv = TestEnv::new();
+    testenv.add_entry(
+        "git-commit",
+        "# git commit\n\n> Commit files.\n\n- Commit staged files:\n\n`git commit`\n\n\
+         - Amend the last commit:\n\n`git commit --amend`\n",
+    );
+
+    testenv
+        .command()
+        .args(&["git", "commit", "amend"])
+        .assert()
+        .success()
+        .stdout(contains("Commit files."))
+        .stderr(similar(
+            "No page for `git-commit-amend`, showing `git-commit` instead.\n",
+        ));
+    testenv
+        .command()
+        .args(&["--quiet", "git", "commit", "amend", "all"])
+        .assert()
+        .success()
+        .stdout(contains("Commit files."))
+        .stderr(is_empty());
+
+    // Hyphens within a word are part of the page name
+    testenv
+        .command()
+        .args(&["git-commitx"])
+        .assert()
+        .failure()
+        .stderr(contains("Page git-commitx not found in cache"));
+    testenv
+        .command()
+        .args(&["gti", "commit"])
+        .assert()
+        .failure()
+        .stderr(contains("Page gti-commit not found in cache"));
+
+    // Examples that mention the dropped words are highlighted
+    testenv.write_config("[style.highlight]\nbold = true");
+    testenv
+        .command()
+        .args(&["--color", "always", "git", "commit", "amend"])
+        .assert()
+        .success()
+        .stdout(
+            contains("  \x1b[1mAmend the last commit:\x1b[0m")
+                .and(contains("  Commit staged files:"))
+                .and(contains("      \x1b[1mgit commit --amend\x1b[0m")),
+        );
+}